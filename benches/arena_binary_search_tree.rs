@@ -0,0 +1,139 @@
+use algorithms::binary_search_tree::{ArenaBinarySearchTree, BinarySearchTree};
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind};
+
+fn read_test_data(path: &str) -> Result<Vec<i32>, Error> {
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+    let mut v = Vec::<i32>::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let n = line
+            .trim()
+            .parse()
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        v.push(n);
+    }
+
+    Ok(v)
+}
+
+fn random_data(count: usize) -> Vec<i32> {
+    let mut rng = StdRng::seed_from_u64(42);
+    (0..count).map(|_| rng.gen()).collect()
+}
+
+fn insert(c: &mut Criterion, name: &str, data: &[i32], sample_size: usize) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(sample_size);
+    group.bench_function("ArenaBinarySearchTree", |b| {
+        b.iter(|| {
+            let mut tree = ArenaBinarySearchTree::new();
+            for &item in data {
+                tree.insert(item);
+            }
+            tree
+        })
+    });
+    group.bench_function("BinarySearchTree", |b| {
+        b.iter(|| {
+            let mut tree = BinarySearchTree::new();
+            for &item in data {
+                tree.insert(item);
+            }
+            tree
+        })
+    });
+    group.finish();
+}
+
+fn insert_and_delete(c: &mut Criterion, name: &str, data: &[i32], sample_size: usize) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(sample_size);
+    group.bench_function("ArenaBinarySearchTree", |b| {
+        b.iter(|| {
+            let mut tree = ArenaBinarySearchTree::new();
+            for &item in data {
+                tree.insert(item);
+            }
+            for &item in data {
+                tree.delete(item);
+            }
+            tree
+        })
+    });
+    group.bench_function("BinarySearchTree", |b| {
+        b.iter(|| {
+            let mut tree = BinarySearchTree::new();
+            for &item in data {
+                tree.insert(item);
+            }
+            for &item in data {
+                tree.delete(item);
+            }
+            tree
+        })
+    });
+    group.finish();
+}
+
+fn lookup(c: &mut Criterion, name: &str, data: &[i32], sample_size: usize) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(sample_size);
+    // Both trees are built by inserting in the same order, so they have the same shape
+    let mut arena_tree = ArenaBinarySearchTree::new();
+    let mut boxed_tree = BinarySearchTree::new();
+    for &item in data {
+        arena_tree.insert(item);
+        boxed_tree.insert(item);
+    }
+    group.bench_function("ArenaBinarySearchTree", |b| {
+        b.iter(|| {
+            data.iter()
+                .filter(|item| arena_tree.contains(*item))
+                .count()
+        })
+    });
+    group.bench_function("BinarySearchTree", |b| {
+        b.iter(|| {
+            data.iter()
+                .filter(|item| boxed_tree.contains(*item))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn insert_ten_thousand(c: &mut Criterion) {
+    let data = read_test_data("benches/data/sorting_10K.in").unwrap();
+    insert(c, "Arena tree - Insert 10K", &data, 30);
+}
+
+fn insert_and_delete_ten_thousand(c: &mut Criterion) {
+    let data = read_test_data("benches/data/sorting_10K.in").unwrap();
+    insert_and_delete(c, "Arena tree - Insert and delete 10K", &data, 30);
+}
+
+fn insert_one_million(c: &mut Criterion) {
+    let data = random_data(1_000_000);
+    insert(c, "Arena tree - Insert 1M random", &data, 10);
+}
+
+fn lookup_one_million(c: &mut Criterion) {
+    let data = random_data(1_000_000);
+    lookup(c, "Arena tree - Look up 1M random", &data, 10);
+}
+
+criterion_group!(
+    benches,
+    insert_ten_thousand,
+    insert_and_delete_ten_thousand,
+    insert_one_million,
+    lookup_one_million
+);
+criterion_main!(benches);
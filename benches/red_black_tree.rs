@@ -0,0 +1,124 @@
+use algorithms::binary_search_tree::BinarySearchTree;
+use algorithms::red_black_tree::RedBlackTree;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind};
+
+fn read_test_data(path: &str) -> Result<Vec<i32>, Error> {
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+    let mut v = Vec::<i32>::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let n = line
+            .trim()
+            .parse()
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        v.push(n);
+    }
+
+    Ok(v)
+}
+
+fn insert(c: &mut Criterion, name: &str, data: &[i32]) {
+    let mut group = c.benchmark_group(name);
+    group.bench_function("RedBlackTree", |b| {
+        b.iter(|| {
+            let mut tree = RedBlackTree::new();
+            for &item in data {
+                tree.insert(item);
+            }
+            tree
+        })
+    });
+    group.bench_function("BinarySearchTree", |b| {
+        b.iter(|| {
+            let mut tree = BinarySearchTree::new();
+            for &item in data {
+                tree.insert(item);
+            }
+            tree
+        })
+    });
+    group.bench_function("BTreeSet", |b| {
+        b.iter(|| {
+            let mut tree = BTreeSet::new();
+            for &item in data {
+                tree.insert(item);
+            }
+            tree
+        })
+    });
+    group.finish();
+}
+
+fn insert_and_delete(c: &mut Criterion, name: &str, data: &[i32]) {
+    let mut group = c.benchmark_group(name);
+    group.bench_function("RedBlackTree", |b| {
+        b.iter(|| {
+            let mut tree = RedBlackTree::new();
+            for &item in data {
+                tree.insert(item);
+            }
+            for &item in data {
+                tree.delete(item);
+            }
+            tree
+        })
+    });
+    group.bench_function("BinarySearchTree", |b| {
+        b.iter(|| {
+            let mut tree = BinarySearchTree::new();
+            for &item in data {
+                tree.insert(item);
+            }
+            for &item in data {
+                tree.delete(item);
+            }
+            tree
+        })
+    });
+    group.bench_function("BTreeSet", |b| {
+        b.iter(|| {
+            let mut tree = BTreeSet::new();
+            for &item in data {
+                tree.insert(item);
+            }
+            for item in data {
+                tree.remove(item);
+            }
+            tree
+        })
+    });
+    group.finish();
+}
+
+fn insert_ten_thousand(c: &mut Criterion) {
+    let data = read_test_data("benches/data/sorting_10K.in").unwrap();
+    insert(c, "Red-black tree - Insert 10K", &data);
+}
+
+fn insert_ten_thousand_sorted(c: &mut Criterion) {
+    let data = read_test_data("benches/data/sorting_sorted_10K.in").unwrap();
+    insert(c, "Red-black tree - Insert 10K Sorted", &data);
+}
+
+fn insert_and_delete_ten_thousand(c: &mut Criterion) {
+    let data = read_test_data("benches/data/sorting_10K.in").unwrap();
+    insert_and_delete(c, "Red-black tree - Insert and delete 10K", &data);
+}
+
+fn insert_and_delete_ten_thousand_sorted(c: &mut Criterion) {
+    let data = read_test_data("benches/data/sorting_sorted_10K.in").unwrap();
+    insert_and_delete(c, "Red-black tree - Insert and delete 10K Sorted", &data);
+}
+
+criterion_group! {
+    name=benches;
+    config = Criterion::default().sample_size(30);
+    targets = insert_ten_thousand, insert_ten_thousand_sorted, insert_and_delete_ten_thousand, insert_and_delete_ten_thousand_sorted
+}
+criterion_main!(benches);
@@ -0,0 +1,130 @@
+use algorithms::binary_search_tree::BinarySearchTree;
+use algorithms::red_black_tree::RedBlackTree;
+use algorithms::splay_tree::SplayTree;
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind};
+
+fn read_test_data(path: &str) -> Result<Vec<i32>, Error> {
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+    let mut v = Vec::<i32>::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let n = line
+            .trim()
+            .parse()
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        v.push(n);
+    }
+
+    Ok(v)
+}
+
+/// Draws `count` lookups from `keys`, where the k:th key is drawn with probability proportional to 1 / k^exponent
+fn zipfian_lookups(keys: &[i32], exponent: f64, count: usize) -> Vec<i32> {
+    let mut cumulative = Vec::with_capacity(keys.len());
+    let mut total = 0.0;
+    for k in 1..=keys.len() {
+        total += 1.0 / (k as f64).powf(exponent);
+        cumulative.push(total);
+    }
+
+    let mut rng = StdRng::seed_from_u64(42);
+    (0..count)
+        .map(|_| {
+            let x = rng.gen::<f64>() * total;
+            let k = cumulative.partition_point(|&sum| sum < x);
+            keys[k.min(keys.len() - 1)]
+        })
+        .collect()
+}
+
+fn lookup(c: &mut Criterion, name: &str, keys: &[i32], lookups: &[i32]) {
+    let mut group = c.benchmark_group(name);
+
+    let mut splay_tree = SplayTree::new();
+    let mut binary_search_tree = BinarySearchTree::new();
+    let mut red_black_tree = RedBlackTree::new();
+    let mut btree_set = BTreeSet::new();
+    for &key in keys {
+        splay_tree.insert(key);
+        binary_search_tree.insert(key);
+        red_black_tree.insert(key);
+        btree_set.insert(key);
+    }
+
+    group.bench_function("SplayTree", |b| {
+        b.iter(|| {
+            lookups
+                .iter()
+                .filter(|key| splay_tree.find(key).is_some())
+                .count()
+        })
+    });
+    group.bench_function("BinarySearchTree", |b| {
+        b.iter(|| {
+            lookups
+                .iter()
+                .filter(|key| binary_search_tree.contains(*key))
+                .count()
+        })
+    });
+    group.bench_function("RedBlackTree", |b| {
+        b.iter(|| {
+            lookups
+                .iter()
+                .filter(|key| red_black_tree.contains(*key))
+                .count()
+        })
+    });
+    group.bench_function("BTreeSet", |b| {
+        b.iter(|| {
+            lookups
+                .iter()
+                .filter(|key| btree_set.contains(*key))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn uniform_lookups(c: &mut Criterion) {
+    let keys = read_test_data("benches/data/sorting_10K.in").unwrap();
+    let lookups = zipfian_lookups(&keys, 0.0, 100_000);
+    lookup(c, "Splay tree - 100K uniform lookups", &keys, &lookups);
+}
+
+fn zipfian_lookups_moderate(c: &mut Criterion) {
+    let keys = read_test_data("benches/data/sorting_10K.in").unwrap();
+    let lookups = zipfian_lookups(&keys, 0.8, 100_000);
+    lookup(
+        c,
+        "Splay tree - 100K Zipfian lookups, s = 0.8",
+        &keys,
+        &lookups,
+    );
+}
+
+fn zipfian_lookups_skewed(c: &mut Criterion) {
+    let keys = read_test_data("benches/data/sorting_10K.in").unwrap();
+    let lookups = zipfian_lookups(&keys, 1.2, 100_000);
+    lookup(
+        c,
+        "Splay tree - 100K Zipfian lookups, s = 1.2",
+        &keys,
+        &lookups,
+    );
+}
+
+criterion_group! {
+    name=benches;
+    config = Criterion::default().sample_size(30);
+    targets = uniform_lookups, zipfian_lookups_moderate, zipfian_lookups_skewed
+}
+criterion_main!(benches);
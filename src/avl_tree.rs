@@ -0,0 +1,882 @@
+use std::borrow::Borrow;
+use std::cmp::{self, Ordering};
+use std::mem;
+use std::ops::RangeBounds;
+
+use crate::binary_search_tree::{above_start, below_end, Augment, Natural};
+
+struct Node<T: Ord, A: Augment<T, ()>> {
+    item: T,
+    height: usize,
+    // Aggregate of all items in the subtree rooted at this node
+    summary: A::Summary,
+    left: AvlTree<T, A>,
+    right: AvlTree<T, A>,
+}
+
+impl<T: Ord, A: Augment<T, ()>> Node<T, A> {
+    fn new(item: T) -> Self {
+        Node {
+            summary: A::summarize(&item, &()),
+            item,
+            height: 1,
+            left: AvlTree::default(),
+            right: AvlTree::default(),
+        }
+    }
+
+    /// Recomputes the height and summary, assuming those of both subtrees are up to date
+    fn update(&mut self) {
+        self.height = 1 + cmp::max(self.left.height(), self.right.height());
+        let mut summary = A::summarize(&self.item, &());
+        if let Some(ref left) = self.left.node {
+            summary = A::combine(&left.summary, &summary);
+        }
+        if let Some(ref right) = self.right.node {
+            summary = A::combine(&summary, &right.summary);
+        }
+        self.summary = summary;
+    }
+}
+
+/// A self-balancing binary search tree
+///
+/// Offers the same operations as `BinarySearchTree`, but rebalances itself on
+/// insertion and deletion so that the heights of the two subtrees of any node
+/// differ by at most one. This bounds the height by roughly 1.44 log2(n), even
+/// when the items are inserted in sorted order.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::avl_tree::AvlTree;
+///
+/// let mut tree = AvlTree::new();
+/// for i in 0..1000 {
+///     tree.insert(i);
+/// }
+/// assert!(tree.contains(&500));
+/// assert!(tree.height() <= 14);
+/// ```
+///
+/// Like `BinarySearchTreeMap`, the tree can keep an aggregate of every subtree,
+/// described by an `Augment` implementation `A` whose values are `()`. The
+/// summaries are recomputed by every rotation, so aggregates over ranges of
+/// items take O(log n) time.
+///
+/// ```
+/// use algorithms::avl_tree::AvlTree;
+/// use algorithms::binary_search_tree::Count;
+///
+/// let mut tree: AvlTree<i32, Count> = AvlTree::default();
+/// for i in 0..1000 {
+///     tree.insert(i);
+/// }
+/// assert_eq!(tree.aggregate(100..200), 100);
+/// ```
+pub struct AvlTree<T: Ord, A: Augment<T, ()> = ()> {
+    node: Option<Box<Node<T, A>>>,
+}
+
+impl<T: Ord, A: Augment<T, ()>> Default for AvlTree<T, A> {
+    fn default() -> Self {
+        Self { node: None }
+    }
+}
+
+impl<T: Ord, A: Augment<T, ()>> Drop for AvlTree<T, A> {
+    fn drop(&mut self) {
+        // Frees the nodes one at a time instead of recursing through the subtrees
+        let mut nodes: Vec<_> = self.node.take().into_iter().collect();
+        while let Some(mut node) = nodes.pop() {
+            nodes.extend(node.left.node.take());
+            nodes.extend(node.right.node.take());
+        }
+    }
+}
+
+impl<T: Ord> AvlTree<T> {
+    /// Creates an empty tree
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: Ord, A: Augment<T, ()>> AvlTree<T, A> {
+    /// Returns the number of nodes on the longest path from the root to a leaf
+    pub fn height(&self) -> usize {
+        self.node.as_ref().map_or(0, |node| node.height)
+    }
+
+    /// Height of the left subtree minus the height of the right subtree
+    fn balance_factor(&self) -> isize {
+        self.node.as_ref().map_or(0, |node| {
+            node.left.height() as isize - node.right.height() as isize
+        })
+    }
+
+    fn rotate_right(&mut self) {
+        let mut node = self.node.take().unwrap();
+        let mut left = node.left.node.take().unwrap();
+        node.left.node = left.right.node.take();
+        node.update();
+        left.right.node = Some(node);
+        left.update();
+        self.node = Some(left);
+    }
+
+    fn rotate_left(&mut self) {
+        let mut node = self.node.take().unwrap();
+        let mut right = node.right.node.take().unwrap();
+        node.right.node = right.left.node.take();
+        node.update();
+        right.left.node = Some(node);
+        right.update();
+        self.node = Some(right);
+    }
+
+    /// Restores the AVL invariant at the root, assuming both subtrees satisfy it
+    fn rebalance(&mut self) {
+        let balance_factor = self.balance_factor();
+        let node = match self.node {
+            Some(ref mut node) => node,
+            None => return,
+        };
+        if balance_factor > 1 {
+            if node.left.balance_factor() < 0 {
+                node.left.rotate_left();
+            }
+            self.rotate_right();
+        } else if balance_factor < -1 {
+            if node.right.balance_factor() > 0 {
+                node.right.rotate_right();
+            }
+            self.rotate_left();
+        } else {
+            node.update();
+        }
+    }
+
+    pub fn insert(&mut self, item: T) {
+        match self.node {
+            Some(ref mut node) => match item.cmp(&node.item) {
+                Ordering::Greater => node.right.insert(item),
+                Ordering::Less => node.left.insert(item),
+                Ordering::Equal => return,
+            },
+            None => {
+                self.node = Some(Box::new(Node::new(item)));
+                return;
+            }
+        }
+        self.rebalance();
+    }
+
+    /// Adds an item, replacing and returning an equal one that was already present
+    pub fn replace(&mut self, item: T) -> Option<T> {
+        let replaced = match self.node {
+            Some(ref mut node) => match item.cmp(&node.item) {
+                Ordering::Greater => node.right.replace(item),
+                Ordering::Less => node.left.replace(item),
+                Ordering::Equal => Some(mem::replace(&mut node.item, item)),
+            },
+            None => {
+                self.node = Some(Box::new(Node::new(item)));
+                return None;
+            }
+        };
+        self.rebalance();
+        replaced
+    }
+
+    fn find_and_delete_min(&mut self) -> T {
+        let node = self.node.as_mut().unwrap();
+        if node.left.node.is_some() {
+            let min = node.left.find_and_delete_min();
+            self.rebalance();
+            min
+        } else {
+            let mut node = self.node.take().unwrap();
+            self.node = node.right.node.take();
+            node.item
+        }
+    }
+
+    fn delete_node(&mut self) -> T {
+        let mut node = self.node.take().unwrap();
+        if node.left.node.is_some() && node.right.node.is_some() {
+            let min = node.right.find_and_delete_min();
+            let item = mem::replace(&mut node.item, min);
+            self.node = Some(node);
+            item
+        } else if node.left.node.is_some() {
+            self.node = node.left.node.take();
+            node.item
+        } else {
+            self.node = node.right.node.take();
+            node.item
+        }
+    }
+
+    pub fn delete(&mut self, item: T) {
+        self.remove(&item);
+    }
+
+    /// Removes the item equal to `item` and returns it
+    pub fn remove<Q>(&mut self, item: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = match self.node {
+            Some(ref mut node) => match item.cmp(node.item.borrow()) {
+                Ordering::Greater => node.right.remove(item),
+                Ordering::Less => node.left.remove(item),
+                Ordering::Equal => Some(self.delete_node()),
+            },
+            None => return None,
+        };
+        self.rebalance();
+        removed
+    }
+
+    /// Returns the stored item equal to `item`
+    pub fn get<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut tree = self;
+        while let Some(ref node) = tree.node {
+            match item.cmp(node.item.borrow()) {
+                Ordering::Greater => tree = &node.right,
+                Ordering::Less => tree = &node.left,
+                Ordering::Equal => return Some(&node.item),
+            }
+        }
+        None
+    }
+
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(item).is_some()
+    }
+
+    /// Combines the summaries of all items inside `range`
+    ///
+    /// Only the paths to the two bounds of the range are visited. Subtrees
+    /// hanging off them contribute their stored summary as a whole, so this
+    /// takes O(log n) calls to `Augment::combine`.
+    pub fn aggregate<Q, R>(&self, range: R) -> A::Summary
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let mut tree = self;
+        while let Some(ref node) = tree.node {
+            if !above_start(&Natural, node.item.borrow(), start) {
+                tree = &node.right;
+            } else if !below_end(&Natural, node.item.borrow(), end) {
+                tree = &node.left;
+            } else {
+                // The paths to the two bounds split here
+                let mut lower_summary = A::identity();
+                let mut lower = &node.left;
+                while let Some(ref node) = lower.node {
+                    if above_start(&Natural, node.item.borrow(), start) {
+                        let item = A::summarize(&node.item, &());
+                        lower_summary =
+                            A::combine(&A::combine(&item, &node.right.summary()), &lower_summary);
+                        lower = &node.left;
+                    } else {
+                        lower = &node.right;
+                    }
+                }
+
+                let mut upper_summary = A::identity();
+                let mut upper = &node.right;
+                while let Some(ref node) = upper.node {
+                    if below_end(&Natural, node.item.borrow(), end) {
+                        let item = A::summarize(&node.item, &());
+                        upper_summary =
+                            A::combine(&upper_summary, &A::combine(&node.left.summary(), &item));
+                        upper = &node.right;
+                    } else {
+                        upper = &node.left;
+                    }
+                }
+
+                let item = A::summarize(&node.item, &());
+                return A::combine(&A::combine(&lower_summary, &item), &upper_summary);
+            }
+        }
+        A::identity()
+    }
+
+    fn summary(&self) -> A::Summary {
+        self.node
+            .as_ref()
+            .map_or_else(A::identity, |node| node.summary.clone())
+    }
+
+    /// Splits a non-empty tree into its left subtree, root item and right subtree
+    pub(crate) fn split_root(&self) -> Option<(&Self, &T, &Self)> {
+        self.node
+            .as_ref()
+            .map(|node| (&node.left, &node.item, &node.right))
+    }
+
+    /// Returns the summary of all items without cloning it
+    pub(crate) fn root_summary(&self) -> Option<&A::Summary> {
+        self.node.as_ref().map(|node| &node.summary)
+    }
+
+    /// Joins two trees and an item, given that `left < item < right`
+    ///
+    /// Descends along the spine of the taller tree until reaching a subtree
+    /// whose height is within one of the shorter tree, and places the item
+    /// there. Rebalancing on the way back up takes O(|h(left) - h(right)| + 1).
+    fn join(mut left: Self, item: T, mut right: Self) -> Self {
+        if left.height() > right.height() + 1 {
+            let node = left.node.as_mut().unwrap();
+            let inner = mem::take(&mut node.right);
+            node.right = Self::join(inner, item, right);
+            left.rebalance();
+            left
+        } else if right.height() > left.height() + 1 {
+            let node = right.node.as_mut().unwrap();
+            let inner = mem::take(&mut node.left);
+            node.left = Self::join(left, item, inner);
+            right.rebalance();
+            right
+        } else {
+            let mut node = Node::new(item);
+            node.left = left;
+            node.right = right;
+            node.update();
+            Self {
+                node: Some(Box::new(node)),
+            }
+        }
+    }
+
+    /// Joins two trees, given that every item of `left` is less than every item of `right`
+    fn join_trees(left: Self, mut right: Self) -> Self {
+        if right.node.is_none() {
+            return left;
+        }
+        let min = right.find_and_delete_min();
+        Self::join(left, min, right)
+    }
+
+    /// Splits the tree into the items less than `item`, the item itself and the items greater than it
+    fn split(mut self, item: &T) -> (Self, Option<T>, Self) {
+        let node = match self.node.take() {
+            Some(node) => *node,
+            None => return (Self::default(), None, Self::default()),
+        };
+        match item.cmp(&node.item) {
+            Ordering::Less => {
+                let (less, found, greater) = node.left.split(item);
+                (less, found, Self::join(greater, node.item, node.right))
+            }
+            Ordering::Greater => {
+                let (less, found, greater) = node.right.split(item);
+                (Self::join(node.left, node.item, less), found, greater)
+            }
+            Ordering::Equal => (node.left, Some(node.item), node.right),
+        }
+    }
+
+    /// Returns a tree with the items in `self` or `other`
+    ///
+    /// Like the other bulk set operations, this splits `self` around the root
+    /// of `other`, recurses on both halves and joins the results, without
+    /// inserting items one by one. Combining trees of sizes m <= n takes
+    /// O(m log(n / m + 1)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::avl_tree::AvlTree;
+    ///
+    /// let mut evens = AvlTree::new();
+    /// let mut small = AvlTree::new();
+    /// for i in 0..10 {
+    ///     evens.insert(2 * i);
+    ///     small.insert(i);
+    /// }
+    ///
+    /// let union = evens.union(small);
+    /// assert_eq!(union.iter().count(), 15);
+    /// assert!(union.contains(&7));
+    /// assert!(union.contains(&18));
+    /// ```
+    pub fn union(self, mut other: Self) -> Self {
+        let node = match other.node.take() {
+            Some(node) => *node,
+            None => return self,
+        };
+        if self.node.is_none() {
+            return Self {
+                node: Some(Box::new(node)),
+            };
+        }
+        let (less, _, greater) = self.split(&node.item);
+        let left = less.union(node.left);
+        let right = greater.union(node.right);
+        Self::join(left, node.item, right)
+    }
+
+    /// Returns a tree with the items in both `self` and `other`
+    pub fn intersection(self, mut other: Self) -> Self {
+        let node = match other.node.take() {
+            Some(node) if self.node.is_some() => *node,
+            _ => return Self::default(),
+        };
+        let (less, found, greater) = self.split(&node.item);
+        let left = less.intersection(node.left);
+        let right = greater.intersection(node.right);
+        match found {
+            Some(item) => Self::join(left, item, right),
+            None => Self::join_trees(left, right),
+        }
+    }
+
+    /// Returns a tree with the items in `self` but not in `other`
+    pub fn difference(self, mut other: Self) -> Self {
+        let node = match other.node.take() {
+            Some(node) if self.node.is_some() => *node,
+            _ => return self,
+        };
+        let (less, _, greater) = self.split(&node.item);
+        let left = less.difference(node.left);
+        let right = greater.difference(node.right);
+        Self::join_trees(left, right)
+    }
+
+    /// Returns a tree with the items in exactly one of `self` and `other`
+    pub fn symmetric_difference(self, mut other: Self) -> Self {
+        let node = match other.node.take() {
+            Some(node) => *node,
+            None => return self,
+        };
+        if self.node.is_none() {
+            return Self {
+                node: Some(Box::new(node)),
+            };
+        }
+        let (less, found, greater) = self.split(&node.item);
+        let left = less.symmetric_difference(node.left);
+        let right = greater.symmetric_difference(node.right);
+        match found {
+            Some(_) => Self::join_trees(left, right),
+            None => Self::join(left, node.item, right),
+        }
+    }
+
+    /// Returns an iterator over the items in sorted order
+    pub fn iter(&self) -> Iter<'_, T, A> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left_spine(self);
+        iter
+    }
+}
+
+/// An in-order iterator over the items of an `AvlTree`
+pub struct Iter<'a, T: Ord, A: Augment<T, ()> = ()> {
+    stack: Vec<&'a Node<T, A>>,
+}
+
+impl<'a, T: Ord, A: Augment<T, ()>> Iter<'a, T, A> {
+    fn push_left_spine(&mut self, mut tree: &'a AvlTree<T, A>) {
+        while let Some(ref node) = tree.node {
+            self.stack.push(node);
+            tree = &node.left;
+        }
+    }
+}
+
+impl<'a, T: Ord, A: Augment<T, ()>> Iterator for Iter<'a, T, A> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        Some(&node.item)
+    }
+}
+
+impl<'a, T: Ord, A: Augment<T, ()>> IntoIterator for &'a AvlTree<T, A> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_search_tree::Count;
+
+    /// Checks that heights are correct and balanced, and returns the tree height
+    fn assert_balanced<T: Ord, A: Augment<T, ()>>(tree: &AvlTree<T, A>) -> usize {
+        match tree.node {
+            Some(ref node) => {
+                let left = assert_balanced(&node.left);
+                let right = assert_balanced(&node.right);
+                assert!(
+                    (left as isize - right as isize).abs() <= 1,
+                    "Unbalanced node with subtree heights {} and {}",
+                    left,
+                    right
+                );
+                assert_eq!(node.height, 1 + cmp::max(left, right));
+                node.height
+            }
+            None => 0,
+        }
+    }
+
+    /// The height of an AVL tree with n nodes is below 1.4405 log2(n + 2) - 0.3277
+    fn max_avl_height(n: usize) -> usize {
+        (1.4405 * ((n + 2) as f64).log2() - 0.3277) as usize
+    }
+
+    #[test]
+    fn empty_tree_doesnt_contain_element() {
+        let tree: AvlTree<i32> = AvlTree::new();
+        assert!(!tree.contains(&1));
+        assert_eq!(tree.height(), 0);
+    }
+
+    #[test]
+    fn multiple_inserted_items_exist() {
+        let mut tree = AvlTree::new();
+        for i in &[5, 6, 3, 4, 2, 1] {
+            tree.insert(*i);
+        }
+
+        for i in 1..7 {
+            assert!(tree.contains(&i));
+        }
+        assert!(!tree.contains(&7));
+    }
+
+    #[test]
+    fn duplicate_insert_is_ignored() {
+        let mut tree = AvlTree::new();
+        tree.insert(1);
+        tree.insert(1);
+        tree.delete(1);
+
+        assert!(!tree.contains(&1));
+    }
+
+    #[test]
+    fn replace_returns_the_previous_item() {
+        let mut tree = AvlTree::new();
+        assert_eq!(tree.replace(2), None);
+        assert_eq!(tree.replace(1), None);
+        assert_eq!(tree.replace(2), Some(2));
+        assert_eq!(sorted(&tree), vec![1, 2]);
+    }
+
+    #[test]
+    fn deleted_item_does_not_exist() {
+        let mut tree = AvlTree::new();
+        tree.insert(2);
+        tree.insert(1);
+        tree.insert(5);
+        tree.insert(3);
+        tree.insert(4);
+
+        tree.delete(2);
+
+        assert!(!tree.contains(&2));
+        for i in &[1, 3, 4, 5] {
+            assert!(tree.contains(i));
+        }
+        assert_balanced(&tree);
+    }
+
+    #[test]
+    fn delete_item_that_does_not_exist() {
+        let mut tree = AvlTree::new();
+        tree.delete(1);
+        tree.insert(2);
+        tree.delete(1);
+
+        assert!(tree.contains(&2));
+    }
+
+    #[test]
+    fn remove_returns_the_item_and_lookups_take_borrowed_forms() {
+        let mut tree = AvlTree::new();
+        for item in &["b", "a", "d", "c"] {
+            tree.insert(item.to_string());
+        }
+
+        assert!(tree.contains("a"));
+        assert!(!tree.contains("e"));
+        assert_eq!(tree.get("c").map(String::as_str), Some("c"));
+
+        assert_eq!(tree.remove("b"), Some(String::from("b")));
+        assert_eq!(tree.remove("b"), None);
+        assert!(!tree.contains("b"));
+        assert_eq!(tree.iter().count(), 3);
+        assert_balanced(&tree);
+    }
+
+    #[test]
+    fn height_is_logarithmic_after_sorted_inserts() {
+        let n = 10_000;
+        let mut tree = AvlTree::new();
+        for i in 0..n {
+            tree.insert(i);
+        }
+
+        assert_balanced(&tree);
+        assert!(tree.height() <= max_avl_height(n));
+        assert!((0..n).all(|i| tree.contains(&i)));
+    }
+
+    #[test]
+    fn height_is_logarithmic_after_reverse_sorted_inserts() {
+        let n = 10_000;
+        let mut tree = AvlTree::new();
+        for i in (0..n).rev() {
+            tree.insert(i);
+        }
+
+        assert_balanced(&tree);
+        assert!(tree.height() <= max_avl_height(n));
+        assert!((0..n).all(|i| tree.contains(&i)));
+    }
+
+    #[test]
+    fn tree_stays_balanced_during_deletes() {
+        let n = 1000;
+        let mut tree = AvlTree::new();
+        for i in 0..n {
+            tree.insert(i);
+        }
+        for i in (0..n).filter(|i| i % 3 != 0) {
+            tree.delete(i);
+            assert_balanced(&tree);
+        }
+
+        for i in 0..n {
+            assert_eq!(tree.contains(&i), i % 3 == 0);
+        }
+        assert!(tree.height() <= max_avl_height(n / 3 + 1));
+    }
+
+    #[test]
+    fn iter_is_sorted() {
+        let mut tree = AvlTree::new();
+        for i in &[5, 1, 9, 3, 7, 2, 8] {
+            tree.insert(*i);
+        }
+        assert_eq!(
+            tree.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 5, 7, 8, 9]
+        );
+    }
+
+    fn tree(items: impl IntoIterator<Item = usize>) -> AvlTree<usize> {
+        let mut tree = AvlTree::new();
+        for item in items {
+            tree.insert(item);
+        }
+        tree
+    }
+
+    fn sorted(tree: &AvlTree<usize>) -> Vec<usize> {
+        tree.iter().copied().collect()
+    }
+
+    #[test]
+    fn join_trees_of_different_heights() {
+        for split in &[0, 1, 10, 500, 998, 999] {
+            let left = tree(0..*split);
+            let right = tree(*split + 1..1000);
+            let joined = AvlTree::join(left, *split, right);
+
+            assert_balanced(&joined);
+            assert_eq!(sorted(&joined), (0..1000).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn split_keeps_both_halves_balanced() {
+        let (less, found, greater) = tree(0..1000).split(&400);
+        assert_eq!(found, Some(400));
+        assert_balanced(&less);
+        assert_balanced(&greater);
+        assert_eq!(sorted(&less), (0..400).collect::<Vec<_>>());
+        assert_eq!(sorted(&greater), (401..1000).collect::<Vec<_>>());
+
+        let (less, found, greater) = tree((0..100).map(|i| 2 * i)).split(&51);
+        assert_eq!(found, None);
+        assert_eq!(less.iter().count(), 26);
+        assert_eq!(greater.iter().count(), 74);
+    }
+
+    #[test]
+    fn bulk_set_operations_match_btree_set() {
+        use std::collections::BTreeSet;
+
+        let a_items: Vec<_> = (0..2000).map(|i| (i * 37) % 1511).collect();
+        let b_items: Vec<_> = (0..300).map(|i| (i * 53) % 1999).collect();
+        let a_set: BTreeSet<_> = a_items.iter().copied().collect();
+        let b_set: BTreeSet<_> = b_items.iter().copied().collect();
+        let a = || tree(a_items.iter().copied());
+        let b = || tree(b_items.iter().copied());
+
+        let union = a().union(b());
+        assert_balanced(&union);
+        assert_eq!(
+            sorted(&union),
+            a_set.union(&b_set).copied().collect::<Vec<_>>()
+        );
+
+        let intersection = a().intersection(b());
+        assert_balanced(&intersection);
+        assert_eq!(
+            sorted(&intersection),
+            a_set.intersection(&b_set).copied().collect::<Vec<_>>()
+        );
+
+        let difference = b().difference(a());
+        assert_balanced(&difference);
+        assert_eq!(
+            sorted(&difference),
+            b_set.difference(&a_set).copied().collect::<Vec<_>>()
+        );
+
+        let symmetric_difference = a().symmetric_difference(b());
+        assert_balanced(&symmetric_difference);
+        assert_eq!(
+            sorted(&symmetric_difference),
+            a_set
+                .symmetric_difference(&b_set)
+                .copied()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn bulk_set_operations_with_empty_trees() {
+        assert_eq!(sorted(&tree(0..3).union(AvlTree::new())), vec![0, 1, 2]);
+        assert_eq!(sorted(&AvlTree::new().union(tree(0..3))), vec![0, 1, 2]);
+        assert_eq!(sorted(&tree(0..3).intersection(AvlTree::new())), vec![]);
+        assert_eq!(
+            sorted(&tree(0..3).difference(AvlTree::new())),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            sorted(&AvlTree::new().symmetric_difference(tree(0..3))),
+            vec![0, 1, 2]
+        );
+    }
+
+    /// Concatenates the items in order, which is not commutative
+    struct Concat;
+
+    impl Augment<usize, ()> for Concat {
+        type Summary = Vec<usize>;
+
+        fn identity() -> Vec<usize> {
+            Vec::new()
+        }
+
+        fn summarize(item: &usize, _value: &()) -> Vec<usize> {
+            vec![*item]
+        }
+
+        fn combine(left: &Vec<usize>, right: &Vec<usize>) -> Vec<usize> {
+            left.iter().chain(right).copied().collect()
+        }
+    }
+
+    /// Checks that every summary concatenates the items of its subtree, and returns them
+    fn assert_summaries(tree: &AvlTree<usize, Concat>) -> Vec<usize> {
+        match tree.node {
+            Some(ref node) => {
+                let mut items = assert_summaries(&node.left);
+                items.push(node.item);
+                items.extend(assert_summaries(&node.right));
+                assert_eq!(node.summary, items);
+                items
+            }
+            None => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn summaries_are_updated_by_rotations() {
+        let n = 300;
+        let mut tree: AvlTree<usize, Concat> = AvlTree::default();
+        for i in 0..n {
+            tree.insert(i);
+        }
+        assert_summaries(&tree);
+        for i in (0..n).filter(|i| i % 3 != 0) {
+            tree.delete(i);
+        }
+        assert_balanced(&tree);
+        assert_summaries(&tree);
+
+        let items: Vec<_> = (0..n).filter(|i| i % 3 == 0).collect();
+        for &(start, end) in &[(0, n), (1, 2), (10, 11), (31, 200), (150, 151), (299, n)] {
+            let expected: Vec<_> = items
+                .iter()
+                .copied()
+                .filter(|i| (start..end).contains(i))
+                .collect();
+            assert_eq!(tree.aggregate(start..end), expected);
+        }
+        assert_eq!(
+            tree.aggregate(..=30),
+            vec![0, 3, 6, 9, 12, 15, 18, 21, 24, 27, 30]
+        );
+        assert_eq!(tree.aggregate(297..), vec![297]);
+    }
+
+    #[test]
+    fn summaries_are_updated_by_bulk_set_operations() {
+        let counted = |items: &mut dyn Iterator<Item = usize>| {
+            let mut tree: AvlTree<usize, Count> = AvlTree::default();
+            for item in items {
+                tree.insert(item);
+            }
+            tree
+        };
+        let a = || counted(&mut (0..1000).map(|i| i * 2));
+        let b = || counted(&mut (0..1000).map(|i| i * 3));
+
+        let union = a().union(b());
+        assert_balanced(&union);
+        assert_eq!(union.aggregate(..), union.iter().count());
+        assert_eq!(union.aggregate(0..12), 8);
+
+        let intersection = a().intersection(b());
+        assert_eq!(intersection.aggregate(..), 334);
+        assert_eq!(intersection.aggregate(6..=60), 10);
+
+        let difference = a().difference(b());
+        assert_eq!(difference.aggregate(..), 666);
+
+        let symmetric_difference = a().symmetric_difference(b());
+        assert_eq!(symmetric_difference.aggregate(..), 1332);
+        assert_eq!(symmetric_difference.aggregate(..12), 6);
+    }
+}
@@ -1,117 +1,599 @@
+use std::borrow::Borrow;
 use std::cmp::Ordering;
+use std::iter::FromIterator;
+use std::mem;
 
-struct Node<T: Ord> {
+use crate::merge_sort::merge_sort_by;
+
+mod arena;
+mod augment;
+mod compare;
+mod cursor;
+#[cfg(test)]
+mod differential;
+mod iter;
+mod map;
+mod multiset;
+mod persistent;
+mod range;
+mod render;
+mod set_ops;
+mod split;
+mod validate;
+
+pub use self::arena::{ArenaBinarySearchTree, ArenaIter, ArenaRange};
+pub use self::augment::{Augment, Count, MaxValue, Sum};
+pub use self::compare::{CaseInsensitive, Compare, Natural, Reverse};
+pub use self::cursor::{Cursor, CursorMut};
+pub use self::iter::{IntoIter, Iter, IterMut, LevelOrderIter, PostorderIter, PreorderIter};
+pub use self::map::{BinarySearchTreeMap, Entry, OccupiedEntry, VacantEntry};
+pub use self::multiset::{BinarySearchMultiset, MultisetCounts, MultisetIter};
+pub use self::persistent::{PersistentBinarySearchTree, PersistentIter};
+pub use self::range::Range;
+pub(crate) use self::range::{above_start, below_end};
+pub use self::set_ops::{Difference, Intersection, SymmetricDifference, Union};
+pub use self::validate::{DepthStats, ValidationError};
+
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
     item: T,
-    left: BinarySearchTree<T>,
-    right: BinarySearchTree<T>,
+    // Number of items in the subtree rooted at this node
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
 }
 
-impl<T: Ord> Node<T> {
+impl<T> Node<T> {
     fn new(item: T) -> Self {
         Node {
             item,
-            left: BinarySearchTree::new(),
-            right: BinarySearchTree::new(),
+            size: 1,
+            left: None,
+            right: None,
+        }
+    }
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+fn leftmost<T>(mut link: &Link<T>) -> Option<&T> {
+    let mut item = None;
+    while let Some(ref node) = link {
+        item = Some(&node.item);
+        link = &node.left;
+    }
+    item
+}
+
+fn rightmost<T>(mut link: &Link<T>) -> Option<&T> {
+    let mut item = None;
+    while let Some(ref node) = link {
+        item = Some(&node.item);
+        link = &node.right;
+    }
+    item
+}
+
+/// Counts the items of a subtree for which `predicate` holds, given that it holds for a prefix of the items
+fn count_prefix<T, F: Fn(&T) -> bool>(mut link: &Link<T>, predicate: F) -> usize {
+    let mut count = 0;
+    while let Some(ref node) = link {
+        if predicate(&node.item) {
+            count += 1 + size(&node.left);
+            link = &node.right;
+        } else {
+            link = &node.left;
         }
     }
+    count
 }
 
-pub struct BinarySearchTree<T: Ord> {
-    node: Option<Box<Node<T>>>,
+/// Removes the smallest item of a non-empty subtree
+fn find_and_delete_min<T>(mut link: &mut Link<T>) -> T {
+    while link.as_ref().unwrap().left.is_some() {
+        let node = link.as_mut().unwrap();
+        node.size -= 1;
+        link = &mut node.left;
+    }
+    let mut node = link.take().unwrap();
+    *link = node.right.take();
+    node.item
+}
+
+/// Removes the largest item of a non-empty subtree
+fn find_and_delete_max<T>(mut link: &mut Link<T>) -> T {
+    while link.as_ref().unwrap().right.is_some() {
+        let node = link.as_mut().unwrap();
+        node.size -= 1;
+        link = &mut node.right;
+    }
+    let mut node = link.take().unwrap();
+    *link = node.left.take();
+    node.item
+}
+
+/// Removes the item at the root of a non-empty subtree
+fn delete_node<T>(link: &mut Link<T>) -> T {
+    let node = link.as_mut().unwrap();
+    if node.left.is_some() && node.right.is_some() {
+        node.size -= 1;
+        let successor = find_and_delete_min(&mut node.right);
+        mem::replace(&mut node.item, successor)
+    } else {
+        let mut node = link.take().unwrap();
+        *link = node.left.take().or_else(|| node.right.take());
+        node.item
+    }
+}
+
+/// Drops the nodes of a subtree one at a time, since dropping them recursively could overflow the stack
+fn free<T>(link: Link<T>) {
+    let mut nodes: Vec<_> = link.into_iter().collect();
+    while let Some(mut node) = nodes.pop() {
+        nodes.extend(node.left.take());
+        nodes.extend(node.right.take());
+    }
+}
+
+/// Builds a subtree of minimum height from exactly `len` items in strictly increasing order
+fn from_sorted_exact<T, I: Iterator<Item = T>>(items: &mut I, len: usize) -> Link<T> {
+    if len == 0 {
+        return None;
+    }
+    let left = from_sorted_exact(items, len / 2);
+    let item = items.next().unwrap();
+    let right = from_sorted_exact(items, len - len / 2 - 1);
+    Some(Box::new(Node {
+        item,
+        size: len,
+        left,
+        right,
+    }))
 }
 
-impl<T: Ord> Default for BinarySearchTree<T> {
+/// Bounds the work of single-item updates by the cost of rebuilding a tree
+///
+/// An update walks the path to its node, which in an unbalanced tree can be as
+/// long as the tree itself. A batch is applied one update at a time while the
+/// walked paths fit in the budget, and the rest of it by rebuilding.
+struct RebuildBudget {
+    remaining: usize,
+}
+
+impl RebuildBudget {
+    fn new(rebuild_cost: usize) -> Self {
+        Self {
+            remaining: rebuild_cost,
+        }
+    }
+
+    /// Spends the length of one path, returning whether it fit in the budget
+    fn spend(&mut self, path_length: usize) -> bool {
+        match self.remaining.checked_sub(path_length) {
+            Some(remaining) => {
+                self.remaining = remaining;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// An unbalanced binary search tree
+///
+/// Items are ordered by their `Ord` implementation, or by the `Compare`
+/// implementation `C` when one is given. A tree with a custom ordering is
+/// created with `with_comparator`, or with `default` or by collecting items
+/// into it if the ordering implements `Default`.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::binary_search_tree::{BinarySearchTree, CaseInsensitive, Reverse};
+///
+/// let mut descending = BinarySearchTree::<_, Reverse>::default();
+/// descending.extend(vec![2, 3, 1]);
+/// assert_eq!(descending.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+///
+/// let words: BinarySearchTree<_, CaseInsensitive> = vec!["b", "A", "a", "C"].into_iter().collect();
+/// assert_eq!(words.len(), 3);
+/// assert!(words.contains("B"));
+/// ```
+pub struct BinarySearchTree<T, C: Compare<T> = Natural> {
+    root: Link<T>,
+    // The ordering of all items, shared by every subtree
+    comparator: C,
+}
+
+impl<T, C: Compare<T> + Default> Default for BinarySearchTree<T, C> {
     fn default() -> Self {
-        Self::new()
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T, C: Compare<T>> Drop for BinarySearchTree<T, C> {
+    fn drop(&mut self) {
+        free(self.root.take());
     }
 }
 
 impl<T: Ord> BinarySearchTree<T> {
     pub fn new() -> Self {
-        Self { node: None }
+        Self::default()
+    }
+
+    /// Builds a tree of minimum height from sorted items, in O(n) time
+    ///
+    /// Equal items are kept only once, so the input may contain duplicates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the items are not sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::binary_search_tree::BinarySearchTree;
+    ///
+    /// let bst = BinarySearchTree::from_sorted_iter(0..1000);
+    /// assert_eq!(bst.len(), 1000);
+    /// assert_eq!(bst.select(500), Some(&500));
+    /// ```
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+        let mut sorted: Vec<T> = Vec::new();
+        for item in items {
+            if let Some(last) = sorted.last() {
+                assert!(*last <= item, "Items are not sorted");
+                if *last == item {
+                    continue;
+                }
+            }
+            sorted.push(item);
+        }
+        Self::from_sorted_vec(sorted, Natural)
+    }
+}
+
+impl<T, C: Compare<T>> BinarySearchTree<T, C> {
+    /// Creates an empty tree ordered by `comparator`
+    pub fn with_comparator(comparator: C) -> Self {
+        Self {
+            root: None,
+            comparator,
+        }
+    }
+
+    /// Creates an empty tree with the same ordering
+    fn empty(&self) -> Self {
+        Self::with_comparator(self.comparator.clone())
+    }
+
+    /// Returns the number of items in the tree
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the stored item equal to `item`
+    pub fn get<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut link = &self.root;
+        while let Some(ref node) = link {
+            match self.comparator.compare(item, node.item.borrow()) {
+                Ordering::Greater => link = &node.right,
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.item),
+            }
+        }
+        None
+    }
+
+    /// Builds a tree of minimum height from items in any order
+    fn from_vec(mut items: Vec<T>, comparator: C) -> Self {
+        if !items
+            .windows(2)
+            .all(|pair| comparator.compare(&pair[0], &pair[1]) != Ordering::Greater)
+        {
+            merge_sort_by(&mut items, |a, b| comparator.compare(a, b));
+        }
+        items.dedup_by(|a, b| comparator.compare(a, b) == Ordering::Equal);
+        Self::from_sorted_vec(items, comparator)
+    }
+
+    /// Builds a tree of minimum height from items in strictly increasing order
+    fn from_sorted_vec(items: Vec<T>, comparator: C) -> Self {
+        let len = items.len();
+        Self {
+            root: from_sorted_exact(&mut items.into_iter(), len),
+            comparator,
+        }
+    }
+
+    /// Returns the number of links from the root to `item`, or to the empty link where it would be inserted
+    fn path_length(&self, item: &T) -> usize {
+        let mut length = 1;
+        let mut link = &self.root;
+        while let Some(ref node) = link {
+            match self.comparator.compare(item, &node.item) {
+                Ordering::Greater => link = &node.right,
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => break,
+            }
+            length += 1;
+        }
+        length
     }
 
     pub fn insert(&mut self, item: T) {
-        let mut bst = self;
-        while let Some(ref mut node) = bst.node {
-            match item.cmp(&node.item) {
-                Ordering::Greater => bst = &mut node.right,
-                Ordering::Less => bst = &mut node.left,
-                Ordering::Equal => return,
+        // Sizes are updated on the way down, so first make sure the item is new
+        if self.get(&item).is_some() {
+            return;
+        }
+        let mut link = &mut self.root;
+        while let Some(ref mut node) = link {
+            node.size += 1;
+            if self.comparator.compare(&item, &node.item) == Ordering::Greater {
+                link = &mut node.right;
+            } else {
+                link = &mut node.left;
             }
         }
-        bst.node = Some(Box::new(Node::new(item)));
+        *link = Some(Box::new(Node::new(item)));
     }
 
-    fn find_and_delete_min(&mut self) -> T {
-        let mut bst = self;
-        while bst.node.as_ref().unwrap().left.node.is_some() {
-            bst = &mut bst.node.as_mut().unwrap().left;
+    pub fn delete(&mut self, item: T) {
+        self.remove(&item);
+    }
+
+    /// Removes the item equal to `item` and returns it
+    pub fn remove<Q>(&mut self, item: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        // Sizes are updated on the way down, so first make sure the item exists
+        self.get(item)?;
+        let mut link = &mut self.root;
+        loop {
+            match self
+                .comparator
+                .compare(item, link.as_ref().unwrap().item.borrow())
+            {
+                Ordering::Greater => {
+                    let node = link.as_mut().unwrap();
+                    node.size -= 1;
+                    link = &mut node.right;
+                }
+                Ordering::Less => {
+                    let node = link.as_mut().unwrap();
+                    node.size -= 1;
+                    link = &mut node.left;
+                }
+                Ordering::Equal => return Some(delete_node(link)),
+            }
         }
-        let mut node = bst.node.take().unwrap();
-        bst.node = node.right.node.take();
-        node.item
     }
 
-    fn delete_node(&mut self) {
-        let node = self.node.as_mut().unwrap();
-        if node.left.node.is_some() && node.right.node.is_some() {
-            node.item = node.right.find_and_delete_min();
-        } else if node.left.node.is_some() {
-            self.node = node.left.node.take();
-        } else if node.right.node.is_some() {
-            self.node = node.right.node.take();
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        self.get(item).is_some()
+    }
+
+    /// Returns the number of items strictly less than `item`
+    pub fn rank<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        count_prefix(&self.root, |other| {
+            self.comparator.compare(other.borrow(), item) == Ordering::Less
+        })
+    }
+
+    /// Returns the `k`:th smallest item, counting from zero
+    pub fn select(&self, mut k: usize) -> Option<&T> {
+        let mut link = &self.root;
+        while let Some(ref node) = link {
+            let left_size = size(&node.left);
+            match k.cmp(&left_size) {
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    link = &node.right;
+                }
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.item),
+            }
+        }
+        None
+    }
+
+    /// Returns the smallest item
+    pub fn min(&self) -> Option<&T> {
+        leftmost(&self.root)
+    }
+
+    /// Returns the largest item
+    pub fn max(&self) -> Option<&T> {
+        rightmost(&self.root)
+    }
+
+    /// Removes and returns the smallest item
+    pub fn pop_min(&mut self) -> Option<T> {
+        if self.root.is_some() {
+            Some(find_and_delete_min(&mut self.root))
         } else {
-            self.node = None;
+            None
         }
     }
 
-    pub fn delete(&mut self, item: T) {
-        let mut bst = self;
-        while bst.node.is_some() {
-            match item.cmp(&bst.node.as_ref().unwrap().item) {
-                Ordering::Greater => bst = &mut bst.node.as_mut().unwrap().right,
-                Ordering::Less => bst = &mut bst.node.as_mut().unwrap().left,
-                Ordering::Equal => bst.delete_node(),
+    /// Removes and returns the largest item
+    pub fn pop_max(&mut self) -> Option<T> {
+        if self.root.is_some() {
+            Some(find_and_delete_max(&mut self.root))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the largest item less than or equal to `item`
+    pub fn floor<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut link = &self.root;
+        let mut floor = None;
+        while let Some(ref node) = link {
+            match self.comparator.compare(item, node.item.borrow()) {
+                Ordering::Greater => {
+                    floor = Some(&node.item);
+                    link = &node.right;
+                }
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.item),
+            }
+        }
+        floor
+    }
+
+    /// Returns the smallest item greater than or equal to `item`
+    pub fn ceiling<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut link = &self.root;
+        let mut ceiling = None;
+        while let Some(ref node) = link {
+            match self.comparator.compare(item, node.item.borrow()) {
+                Ordering::Greater => link = &node.right,
+                Ordering::Less => {
+                    ceiling = Some(&node.item);
+                    link = &node.left;
+                }
+                Ordering::Equal => return Some(&node.item),
             }
         }
+        ceiling
     }
 
-    pub fn contains(&self, item: T) -> bool {
-        let mut bst = self;
-        while let Some(ref node) = bst.node {
-            match item.cmp(&node.item) {
-                Ordering::Greater => bst = &node.right,
-                Ordering::Less => bst = &node.left,
-                Ordering::Equal => return true,
+    /// Returns the largest item strictly less than `item`
+    pub fn predecessor<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut link = &self.root;
+        let mut predecessor = None;
+        while let Some(ref node) = link {
+            if self.comparator.compare(item, node.item.borrow()) == Ordering::Greater {
+                predecessor = Some(&node.item);
+                link = &node.right;
+            } else {
+                link = &node.left;
             }
         }
-        false
+        predecessor
+    }
+
+    /// Returns the smallest item strictly greater than `item`
+    pub fn successor<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut link = &self.root;
+        let mut successor = None;
+        while let Some(ref node) = link {
+            if self.comparator.compare(item, node.item.borrow()) == Ordering::Less {
+                successor = Some(&node.item);
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+        successor
+    }
+}
+
+/// Collects items in any order into a tree of minimum height
+///
+/// Input that is already sorted is detected in a single pass and built in
+/// O(n) time. Other input is sorted first, which takes O(n log n) time.
+impl<T, C: Compare<T> + Default> FromIterator<T> for BinarySearchTree<T, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+        Self::from_vec(items.into_iter().collect(), C::default())
+    }
+}
+
+/// Adds items in any order
+///
+/// Items are inserted one at a time while the paths walked by the insertions
+/// add up to less than the size of the tree and batch. The rest are then
+/// collected into a tree and `append`ed, which takes O(m log m + height) time
+/// if they all lie on one side of the existing ones, and rebuilds the tree in
+/// O(n + m log m) if not.
+impl<T, C: Compare<T>> Extend<T> for BinarySearchTree<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        let items: Vec<T> = items.into_iter().collect();
+        let mut budget = RebuildBudget::new(self.len() + items.len());
+        let mut items = items.into_iter();
+        while let Some(item) = items.next() {
+            if !budget.spend(self.path_length(&item)) {
+                let mut rest: Vec<T> = items.collect();
+                rest.push(item);
+                let mut other = Self::from_vec(rest, self.comparator.clone());
+                self.append(&mut other);
+                return;
+            }
+            self.insert(item);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::thread;
 
     #[test]
     fn empty_tree_doesnt_contain_element() {
-        let bst = BinarySearchTree::new();
-        assert!(!bst.contains(1));
+        let bst: BinarySearchTree<i32> = BinarySearchTree::new();
+        assert!(!bst.contains(&1));
     }
 
     #[test]
     fn inserted_element_exists() {
         let mut bst = BinarySearchTree::new();
         bst.insert(1);
-        assert!(bst.contains(1));
+        assert!(bst.contains(&1));
     }
 
     #[test]
     fn non_insterted_element_does_not_exist() {
         let mut bst = BinarySearchTree::new();
         bst.insert(1);
-        assert!(!bst.contains(2));
+        assert!(!bst.contains(&2));
     }
 
     #[test]
@@ -120,9 +602,9 @@ mod tests {
         bst.insert('a');
         bst.insert('c');
 
-        assert!(bst.contains('a'));
-        assert!(bst.contains('c'));
-        assert!(!bst.contains('b'));
+        assert!(bst.contains(&'a'));
+        assert!(bst.contains(&'c'));
+        assert!(!bst.contains(&'b'));
     }
 
     #[test]
@@ -136,7 +618,7 @@ mod tests {
         bst.insert(1);
 
         for i in 1..7 {
-            assert!(bst.contains(i));
+            assert!(bst.contains(&i));
         }
     }
 
@@ -146,7 +628,7 @@ mod tests {
         bst.insert(1);
         bst.delete(1);
 
-        assert!(!bst.contains(1));
+        assert!(!bst.contains(&1));
     }
 
     #[test]
@@ -163,10 +645,11 @@ mod tests {
         bst.insert(2);
         bst.delete(1);
 
-        assert!(bst.contains(2));
-        assert!(bst.contains(3));
+        assert!(bst.contains(&2));
+        assert!(bst.contains(&3));
 
-        assert!(!bst.contains(1));
+        assert!(!bst.contains(&1));
+        assert_eq!(bst.validate(), Ok(()));
     }
 
     #[test]
@@ -177,10 +660,11 @@ mod tests {
         bst.insert(2);
         bst.delete(3);
 
-        assert!(bst.contains(1));
-        assert!(bst.contains(2));
+        assert!(bst.contains(&1));
+        assert!(bst.contains(&2));
 
-        assert!(!bst.contains(3));
+        assert!(!bst.contains(&3));
+        assert_eq!(bst.validate(), Ok(()));
     }
 
     #[test]
@@ -195,11 +679,12 @@ mod tests {
 
         bst.delete(2);
 
-        assert!(bst.contains(1));
-        assert!(bst.contains(3));
-        assert!(bst.contains(4));
-        assert!(bst.contains(5));
-        assert!(!bst.contains(2));
+        assert!(bst.contains(&1));
+        assert!(bst.contains(&3));
+        assert!(bst.contains(&4));
+        assert!(bst.contains(&5));
+        assert!(!bst.contains(&2));
+        assert_eq!(bst.validate(), Ok(()));
     }
 
     #[test]
@@ -210,9 +695,10 @@ mod tests {
         bst.insert(2);
         bst.delete(1);
 
-        assert!(!bst.contains(1));
-        assert!(bst.contains(2));
-        assert!(bst.contains(3));
+        assert!(!bst.contains(&1));
+        assert!(bst.contains(&2));
+        assert!(bst.contains(&3));
+        assert_eq!(bst.validate(), Ok(()));
     }
 
     #[test]
@@ -223,8 +709,315 @@ mod tests {
         bst.insert(3);
         bst.delete(2);
 
-        assert!(bst.contains(1));
-        assert!(!bst.contains(2));
-        assert!(bst.contains(3));
+        assert!(bst.contains(&1));
+        assert!(!bst.contains(&2));
+        assert!(bst.contains(&3));
+        assert_eq!(bst.validate(), Ok(()));
+    }
+
+    #[test]
+    fn min_and_max_of_empty_tree() {
+        let mut bst: BinarySearchTree<i32> = BinarySearchTree::new();
+        assert_eq!(bst.min(), None);
+        assert_eq!(bst.max(), None);
+        assert_eq!(bst.pop_min(), None);
+        assert_eq!(bst.pop_max(), None);
+    }
+
+    #[test]
+    fn min_and_max() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(5);
+        bst.insert(2);
+        bst.insert(8);
+        bst.insert(3);
+        bst.insert(9);
+
+        assert_eq!(bst.min(), Some(&2));
+        assert_eq!(bst.max(), Some(&9));
+    }
+
+    #[test]
+    fn pop_min_and_pop_max_remove_items() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(5);
+        bst.insert(2);
+        bst.insert(3);
+        bst.insert(8);
+        bst.insert(7);
+
+        assert_eq!(bst.pop_min(), Some(2));
+        assert_eq!(bst.pop_max(), Some(8));
+        assert_eq!(bst.pop_min(), Some(3));
+        assert_eq!(bst.pop_max(), Some(7));
+        assert_eq!(bst.pop_max(), Some(5));
+        assert_eq!(bst.pop_min(), None);
+    }
+
+    #[test]
+    fn pop_root_with_one_child() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(1);
+        bst.insert(2);
+        assert_eq!(bst.pop_min(), Some(1));
+        assert!(bst.contains(&2));
+
+        bst.insert(1);
+        assert_eq!(bst.pop_max(), Some(2));
+        assert!(bst.contains(&1));
+    }
+
+    #[test]
+    fn floor_and_ceiling() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(10);
+        bst.insert(5);
+        bst.insert(15);
+        bst.insert(12);
+
+        assert_eq!(bst.floor(&12), Some(&12));
+        assert_eq!(bst.floor(&11), Some(&10));
+        assert_eq!(bst.floor(&20), Some(&15));
+        assert_eq!(bst.floor(&4), None);
+
+        assert_eq!(bst.ceiling(&12), Some(&12));
+        assert_eq!(bst.ceiling(&11), Some(&12));
+        assert_eq!(bst.ceiling(&1), Some(&5));
+        assert_eq!(bst.ceiling(&16), None);
+    }
+
+    #[test]
+    fn predecessor_and_successor() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(10);
+        bst.insert(5);
+        bst.insert(15);
+        bst.insert(12);
+
+        assert_eq!(bst.predecessor(&12), Some(&10));
+        assert_eq!(bst.predecessor(&11), Some(&10));
+        assert_eq!(bst.predecessor(&5), None);
+
+        assert_eq!(bst.successor(&10), Some(&12));
+        assert_eq!(bst.successor(&13), Some(&15));
+        assert_eq!(bst.successor(&15), None);
+    }
+
+    /// Checks that every node's size matches the number of items below it
+    fn assert_sizes<T: Ord>(bst: &BinarySearchTree<T>) -> usize {
+        assert_link_sizes(&bst.root)
+    }
+
+    fn assert_link_sizes<T>(link: &Link<T>) -> usize {
+        match link {
+            Some(ref node) => {
+                let size = 1 + assert_link_sizes(&node.left) + assert_link_sizes(&node.right);
+                assert_eq!(node.size, size);
+                size
+            }
+            None => 0,
+        }
+    }
+
+    #[test]
+    fn len_of_empty_tree() {
+        let bst: BinarySearchTree<i32> = BinarySearchTree::new();
+        assert_eq!(bst.len(), 0);
+        assert!(bst.is_empty());
+    }
+
+    #[test]
+    fn len_counts_distinct_items() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(2);
+        bst.insert(1);
+        bst.insert(3);
+        bst.insert(2);
+
+        assert_eq!(bst.len(), 3);
+        assert!(!bst.is_empty());
+        assert_sizes(&bst);
+    }
+
+    #[test]
+    fn sizes_are_maintained_by_deletes() {
+        let mut bst = BinarySearchTree::new();
+        for i in &[5, 2, 8, 1, 4, 3, 7, 9, 6] {
+            bst.insert(*i);
+        }
+        bst.delete(10);
+        assert_eq!(bst.len(), 9);
+
+        for i in &[5, 2, 9, 1] {
+            bst.delete(*i);
+            assert_sizes(&bst);
+        }
+        bst.pop_min();
+        bst.pop_max();
+
+        assert_eq!(bst.len(), 3);
+        assert_sizes(&bst);
+    }
+
+    #[test]
+    fn rank() {
+        let mut bst = BinarySearchTree::new();
+        for i in &[50, 20, 80, 10, 30, 70, 90] {
+            bst.insert(*i);
+        }
+
+        assert_eq!(bst.rank(&5), 0);
+        assert_eq!(bst.rank(&10), 0);
+        assert_eq!(bst.rank(&11), 1);
+        assert_eq!(bst.rank(&50), 3);
+        assert_eq!(bst.rank(&85), 6);
+        assert_eq!(bst.rank(&100), 7);
+    }
+
+    #[test]
+    fn select() {
+        let mut bst = BinarySearchTree::new();
+        for i in &[50, 20, 80, 10, 30, 70, 90] {
+            bst.insert(*i);
+        }
+
+        let sorted = [10, 20, 30, 50, 70, 80, 90];
+        for (k, item) in sorted.iter().enumerate() {
+            assert_eq!(bst.select(k), Some(item));
+            assert_eq!(bst.rank(item), k);
+        }
+        assert_eq!(bst.select(7), None);
+    }
+
+    #[test]
+    fn remove_returns_removed_item() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(2);
+        bst.insert(1);
+        bst.insert(3);
+
+        assert_eq!(bst.remove(&2), Some(2));
+        assert_eq!(bst.remove(&2), None);
+        assert_eq!(bst.remove(&4), None);
+        assert_eq!(bst.len(), 2);
+        assert!(bst.contains(&1));
+        assert!(bst.contains(&3));
+    }
+
+    #[test]
+    fn lookups_by_borrowed_form() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(String::from("b"));
+        bst.insert(String::from("a"));
+        bst.insert(String::from("c"));
+
+        assert!(bst.contains("a"));
+        assert!(!bst.contains("d"));
+        assert_eq!(bst.get("c").map(String::as_str), Some("c"));
+        assert_eq!(bst.floor("bb").map(String::as_str), Some("b"));
+        assert_eq!(bst.rank("c"), 2);
+
+        assert_eq!(bst.remove("b"), Some(String::from("b")));
+        assert!(!bst.contains("b"));
+        assert_eq!(bst.len(), 2);
+    }
+
+    /// Returns the number of levels of the tree
+    fn levels<T: Ord>(bst: &BinarySearchTree<T>) -> usize {
+        link_levels(&bst.root)
+    }
+
+    fn link_levels<T>(link: &Link<T>) -> usize {
+        link.as_ref().map_or(0, |node| {
+            1 + std::cmp::max(link_levels(&node.left), link_levels(&node.right))
+        })
+    }
+
+    #[test]
+    fn from_sorted_iter_builds_minimum_height_tree() {
+        for n in 0..70usize {
+            let bst = BinarySearchTree::from_sorted_iter(0..n);
+            assert_sizes(&bst);
+            assert_eq!(
+                bst.iter().copied().collect::<Vec<_>>(),
+                (0..n).collect::<Vec<_>>()
+            );
+            // A tree of height h holds at most 2^h - 1 items
+            let min_levels = (usize::BITS - n.leading_zeros()) as usize;
+            assert_eq!(levels(&bst), min_levels);
+        }
+    }
+
+    #[test]
+    fn from_sorted_iter_skips_duplicates() {
+        let bst = BinarySearchTree::from_sorted_iter(vec![1, 1, 2, 3, 3, 3]);
+        assert_eq!(bst.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Items are not sorted")]
+    fn from_sorted_iter_rejects_unsorted_input() {
+        BinarySearchTree::from_sorted_iter(vec![1, 3, 2]);
+    }
+
+    #[test]
+    fn collect_sorts_and_balances() {
+        let bst: BinarySearchTree<_> = vec![5, 3, 9, 3, 1, 7].into_iter().collect();
+        assert_eq!(bst.iter().copied().collect::<Vec<_>>(), vec![1, 3, 5, 7, 9]);
+        assert_sizes(&bst);
+        assert_eq!(levels(&bst), 3);
+
+        let bst: BinarySearchTree<_> = (0..1000).rev().collect();
+        assert_eq!(levels(&bst), 10);
+    }
+
+    #[test]
+    fn extend_adds_items() {
+        let mut bst: BinarySearchTree<_> = (0..10).collect();
+        bst.extend(10..20);
+        bst.extend(vec![25, 5, 21]);
+
+        let mut expected: Vec<_> = (0..20).collect();
+        expected.extend(vec![21, 25]);
+        assert_eq!(bst.iter().copied().collect::<Vec<_>>(), expected);
+        assert_sizes(&bst);
+    }
+
+    #[test]
+    fn extend_inserts_small_batches() {
+        let mut bst: BinarySearchTree<_> = (0..1023).map(|i| i * 2).collect();
+        let root = bst.root.as_ref().unwrap().item;
+        bst.extend(vec![1, 501, 1001, 2001]);
+
+        // Rebuilding would have moved the median of all items to the root
+        assert_eq!(bst.root.as_ref().unwrap().item, root);
+        assert_eq!(bst.len(), 1027);
+        assert!(bst.contains(&501));
+        assert_sizes(&bst);
+    }
+
+    #[test]
+    fn degenerate_trees_drop_without_overflowing_the_stack() {
+        // Recursing along a path as long as the tree would overflow this stack
+        thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(|| {
+                let mut bst = BinarySearchTree::new();
+                for i in 0..10_000 {
+                    bst.insert(i);
+                }
+                drop(bst);
+
+                // The owning iterator keeps the chain of 1 to 9998 unexpanded
+                let mut bst = BinarySearchTree::new();
+                for i in (0..10_000).map(|i| (i + 9999) % 10_000) {
+                    bst.insert(i);
+                }
+                let mut items = bst.into_iter();
+                assert_eq!(items.next(), Some(0));
+            })
+            .unwrap()
+            .join()
+            .unwrap();
     }
 }
@@ -0,0 +1,1154 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::iter::{FromIterator, FusedIterator};
+use std::mem;
+use std::ops::RangeBounds;
+
+use super::range::{above_start, below_end};
+use super::split::Merge;
+use super::{Compare, Natural, RebuildBudget};
+use crate::merge_sort::merge_sort_by;
+
+// Marks a missing child, and the end of the free list
+const NIL: u32 = u32::MAX;
+
+struct Node<T> {
+    item: T,
+    // Number of items in the subtree rooted at this node
+    size: u32,
+    left: u32,
+    right: u32,
+}
+
+enum Slot<T> {
+    Occupied(Node<T>),
+    // A deleted node, linking to the next free slot
+    Free(u32),
+}
+
+/// An unbalanced binary search tree storing its nodes in a single `Vec`
+///
+/// Offers the lookups, order statistics, range queries, splitting and merging
+/// of `BinarySearchTree`, but nodes link to each other by their index in the
+/// arena instead of through separate heap allocations. There are no cursors,
+/// mutable iterators or set operations. Slots of removed nodes are kept in a
+/// free list and reused by later insertions. Indices are 32 bits, which keeps
+/// nodes of small items compact, so a tree holds at most `u32::MAX - 1` items.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::binary_search_tree::ArenaBinarySearchTree;
+///
+/// let mut tree = ArenaBinarySearchTree::with_capacity(3);
+/// tree.insert(2);
+/// tree.insert(1);
+/// tree.insert(3);
+/// assert_eq!(tree.remove(&2), Some(2));
+///
+/// // The freed slot is reused
+/// tree.insert(4);
+/// assert_eq!(tree.slot_count(), 3);
+/// assert_eq!(tree.iter().copied().collect::<Vec<_>>(), vec![1, 3, 4]);
+/// ```
+pub struct ArenaBinarySearchTree<T, C: Compare<T> = Natural> {
+    slots: Vec<Slot<T>>,
+    root: u32,
+    // Head of the list of free slots
+    free: u32,
+    comparator: C,
+}
+
+impl<T, C: Compare<T> + Default> Default for ArenaBinarySearchTree<T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<T: Ord> ArenaBinarySearchTree<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty tree with room for `capacity` items before reallocating
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+}
+
+impl<T, C: Compare<T>> ArenaBinarySearchTree<T, C> {
+    /// Creates an empty tree ordered by `comparator`
+    pub fn with_comparator(comparator: C) -> Self {
+        Self {
+            slots: Vec::new(),
+            root: NIL,
+            free: NIL,
+            comparator,
+        }
+    }
+
+    /// Returns the number of items in the tree
+    pub fn len(&self) -> usize {
+        self.size(self.root) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root == NIL
+    }
+
+    /// Returns the current number of slots in the arena, occupied and free
+    ///
+    /// Removals free slots without shrinking the arena, while operations that
+    /// rebuild the tree, such as `remove_range`, `split_off` and large
+    /// `extend`s, leave exactly one slot per item.
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the number of slots the arena can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    /// Removes all items, keeping the allocated memory
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.root = NIL;
+        self.free = NIL;
+    }
+
+    /// Builds a tree of minimum height from items in any order
+    fn from_vec(mut items: Vec<T>, comparator: C) -> Self {
+        if !items
+            .windows(2)
+            .all(|pair| comparator.compare(&pair[0], &pair[1]) != Ordering::Greater)
+        {
+            merge_sort_by(&mut items, |a, b| comparator.compare(a, b));
+        }
+        items.dedup_by(|a, b| comparator.compare(a, b) == Ordering::Equal);
+        Self::from_sorted_vec(items, comparator)
+    }
+
+    /// Builds a tree of minimum height from items in strictly increasing order
+    ///
+    /// Nodes are stored in sorted order, so iterating visits the arena front
+    /// to back.
+    fn from_sorted_vec(items: Vec<T>, comparator: C) -> Self {
+        let len = items.len();
+        assert!(len < NIL as usize, "Arena is full");
+        let mut tree = Self {
+            slots: items
+                .into_iter()
+                .map(|item| {
+                    Slot::Occupied(Node {
+                        item,
+                        size: 1,
+                        left: NIL,
+                        right: NIL,
+                    })
+                })
+                .collect(),
+            root: NIL,
+            free: NIL,
+            comparator,
+        };
+        tree.root = tree.link_balanced(0, len as u32);
+        tree
+    }
+
+    /// Links the nodes in slots `start..end` into a tree of minimum height, returning its root
+    fn link_balanced(&mut self, start: u32, end: u32) -> u32 {
+        if start == end {
+            return NIL;
+        }
+        let mid = start + (end - start) / 2;
+        let left = self.link_balanced(start, mid);
+        let right = self.link_balanced(mid + 1, end);
+        let node = self.node_mut(mid);
+        node.size = end - start;
+        node.left = left;
+        node.right = right;
+        mid
+    }
+
+    /// Moves all items out in sorted order, leaving the tree empty
+    fn take_sorted(&mut self) -> Vec<T> {
+        let mut order = Vec::with_capacity(self.len());
+        let mut stack = Vec::new();
+        let mut index = self.root;
+        while index != NIL || !stack.is_empty() {
+            while index != NIL {
+                stack.push(index);
+                index = self.node(index).left;
+            }
+            index = stack.pop().unwrap();
+            order.push(index);
+            index = self.node(index).right;
+        }
+        let mut items: Vec<Option<T>> = self
+            .slots
+            .drain(..)
+            .map(|slot| match slot {
+                Slot::Occupied(node) => Some(node.item),
+                Slot::Free(_) => None,
+            })
+            .collect();
+        self.root = NIL;
+        self.free = NIL;
+        order
+            .into_iter()
+            .map(|index| items[index as usize].take().unwrap())
+            .collect()
+    }
+
+    fn node(&self, index: u32) -> &Node<T> {
+        match self.slots[index as usize] {
+            Slot::Occupied(ref node) => node,
+            Slot::Free(_) => unreachable!("Link to a free slot"),
+        }
+    }
+
+    fn node_mut(&mut self, index: u32) -> &mut Node<T> {
+        match self.slots[index as usize] {
+            Slot::Occupied(ref mut node) => node,
+            Slot::Free(_) => unreachable!("Link to a free slot"),
+        }
+    }
+
+    fn size(&self, index: u32) -> u32 {
+        if index == NIL {
+            0
+        } else {
+            self.node(index).size
+        }
+    }
+
+    /// Stores a new leaf in a free slot, or at the end of the arena
+    fn allocate(&mut self, item: T) -> u32 {
+        let node = Slot::Occupied(Node {
+            item,
+            size: 1,
+            left: NIL,
+            right: NIL,
+        });
+        if self.free != NIL {
+            let index = self.free;
+            self.free = match mem::replace(&mut self.slots[index as usize], node) {
+                Slot::Free(next) => next,
+                Slot::Occupied(_) => unreachable!("Occupied slot in the free list"),
+            };
+            index
+        } else {
+            let index = self.slots.len() as u32;
+            assert!(index != NIL, "Arena is full");
+            self.slots.push(node);
+            index
+        }
+    }
+
+    /// Moves a slot to the free list, returning its item
+    fn release(&mut self, index: u32) -> T {
+        match mem::replace(&mut self.slots[index as usize], Slot::Free(self.free)) {
+            Slot::Occupied(node) => {
+                self.free = index;
+                node.item
+            }
+            Slot::Free(_) => unreachable!("Slot released twice"),
+        }
+    }
+
+    /// Replaces the link from `parent` to its child in `direction`, or the root if there is no parent
+    fn set_child(&mut self, parent: u32, direction: Ordering, child: u32) {
+        if parent == NIL {
+            self.root = child;
+        } else if direction == Ordering::Less {
+            self.node_mut(parent).left = child;
+        } else {
+            self.node_mut(parent).right = child;
+        }
+    }
+
+    /// Returns the index of the node holding `item`
+    fn find<Q>(&self, item: &Q) -> u32
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut index = self.root;
+        while index != NIL {
+            let node = self.node(index);
+            match self.comparator.compare(item, node.item.borrow()) {
+                Ordering::Greater => index = node.right,
+                Ordering::Less => index = node.left,
+                Ordering::Equal => break,
+            }
+        }
+        index
+    }
+
+    /// Returns the stored item equal to `item`
+    pub fn get<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        match self.find(item) {
+            NIL => None,
+            index => Some(&self.node(index).item),
+        }
+    }
+
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        self.find(item) != NIL
+    }
+
+    /// Returns the number of links from the root to `item`, or to the empty link where it would be inserted
+    fn path_length(&self, item: &T) -> usize {
+        let mut length = 1;
+        let mut index = self.root;
+        while index != NIL {
+            let node = self.node(index);
+            match self.comparator.compare(item, &node.item) {
+                Ordering::Greater => index = node.right,
+                Ordering::Less => index = node.left,
+                Ordering::Equal => break,
+            }
+            length += 1;
+        }
+        length
+    }
+
+    /// Returns the number of links from the root to the `k`:th smallest item, which must exist
+    fn nth_path_length(&self, mut k: usize) -> usize {
+        let mut length = 1;
+        let mut index = self.root;
+        loop {
+            let node = self.node(index);
+            let left_size = self.size(node.left) as usize;
+            match k.cmp(&left_size) {
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    index = node.right;
+                }
+                Ordering::Less => index = node.left,
+                Ordering::Equal => return length,
+            }
+            length += 1;
+        }
+    }
+
+    pub fn insert(&mut self, item: T) {
+        // Sizes are updated on the way down, so first make sure the item is new
+        if self.find(&item) != NIL {
+            return;
+        }
+        let mut parent = NIL;
+        let mut direction = Ordering::Less;
+        let mut index = self.root;
+        while index != NIL {
+            direction = self.comparator.compare(&item, &self.node(index).item);
+            let node = self.node_mut(index);
+            node.size += 1;
+            parent = index;
+            index = if direction == Ordering::Greater {
+                node.right
+            } else {
+                node.left
+            };
+        }
+        let leaf = self.allocate(item);
+        self.set_child(parent, direction, leaf);
+    }
+
+    pub fn delete(&mut self, item: T) {
+        self.remove(&item);
+    }
+
+    /// Removes the item equal to `item` and returns it
+    pub fn remove<Q>(&mut self, item: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        // Sizes are updated on the way down, so first make sure the item exists
+        if self.find(item) == NIL {
+            return None;
+        }
+        Some(self.unlink(|tree, index| {
+            tree.comparator
+                .compare(item, tree.node(index).item.borrow())
+        }))
+    }
+
+    /// Removes and returns the `k`:th smallest item, counting from zero
+    fn remove_nth(&mut self, mut k: usize) -> Option<T> {
+        if k >= self.len() {
+            return None;
+        }
+        Some(self.unlink(|tree, index| {
+            let left_size = tree.size(tree.node(index).left) as usize;
+            let ordering = k.cmp(&left_size);
+            if ordering == Ordering::Greater {
+                k -= left_size + 1;
+            }
+            ordering
+        }))
+    }
+
+    /// Removes the node reached by following `direction` from the root, which must exist
+    ///
+    /// `direction` is called once for each node on the path, and returns
+    /// `Ordering::Equal` at the node to remove.
+    fn unlink<F: FnMut(&Self, u32) -> Ordering>(&mut self, mut direction: F) -> T {
+        let mut parent = NIL;
+        let mut parent_direction = Ordering::Less;
+        let mut index = self.root;
+        loop {
+            let ordering = direction(self, index);
+            if ordering == Ordering::Equal {
+                break;
+            }
+            let node = self.node_mut(index);
+            node.size -= 1;
+            parent = index;
+            parent_direction = ordering;
+            index = if ordering == Ordering::Greater {
+                node.right
+            } else {
+                node.left
+            };
+        }
+
+        let (left, right) = {
+            let node = self.node(index);
+            (node.left, node.right)
+        };
+        if left != NIL && right != NIL {
+            // Unlink the successor and move its item into the removed node
+            let mut successor_parent = index;
+            let mut successor = right;
+            while self.node(successor).left != NIL {
+                self.node_mut(successor).size -= 1;
+                successor_parent = successor;
+                successor = self.node(successor).left;
+            }
+            let successor_right = self.node(successor).right;
+            if successor_parent == index {
+                self.node_mut(index).right = successor_right;
+            } else {
+                self.node_mut(successor_parent).left = successor_right;
+            }
+            let successor_item = self.release(successor);
+            let node = self.node_mut(index);
+            node.size -= 1;
+            mem::replace(&mut node.item, successor_item)
+        } else {
+            let child = if left != NIL { left } else { right };
+            self.set_child(parent, parent_direction, child);
+            self.release(index)
+        }
+    }
+
+    /// Returns the smallest item
+    pub fn min(&self) -> Option<&T> {
+        if self.root == NIL {
+            return None;
+        }
+        let mut node = self.node(self.root);
+        while node.left != NIL {
+            node = self.node(node.left);
+        }
+        Some(&node.item)
+    }
+
+    /// Returns the largest item
+    pub fn max(&self) -> Option<&T> {
+        if self.root == NIL {
+            return None;
+        }
+        let mut node = self.node(self.root);
+        while node.right != NIL {
+            node = self.node(node.right);
+        }
+        Some(&node.item)
+    }
+
+    /// Removes and returns the smallest item
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.remove_nth(0)
+    }
+
+    /// Removes and returns the largest item
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.remove_nth(self.len().checked_sub(1)?)
+    }
+
+    /// Returns the number of nodes on the longest path from the root to a leaf
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut stack = vec![(self.root, 0)];
+        while let Some((index, depth)) = stack.pop() {
+            if index == NIL {
+                height = height.max(depth);
+            } else {
+                let node = self.node(index);
+                stack.push((node.left, depth + 1));
+                stack.push((node.right, depth + 1));
+            }
+        }
+        height
+    }
+
+    /// Returns the number of items strictly less than `item`
+    pub fn rank<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut rank = 0;
+        let mut index = self.root;
+        while index != NIL {
+            let node = self.node(index);
+            if self.comparator.compare(node.item.borrow(), item) == Ordering::Less {
+                rank += 1 + self.size(node.left) as usize;
+                index = node.right;
+            } else {
+                index = node.left;
+            }
+        }
+        rank
+    }
+
+    /// Returns the `k`:th smallest item, counting from zero
+    pub fn select(&self, mut k: usize) -> Option<&T> {
+        let mut index = self.root;
+        while index != NIL {
+            let node = self.node(index);
+            let left_size = self.size(node.left) as usize;
+            match k.cmp(&left_size) {
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    index = node.right;
+                }
+                Ordering::Less => index = node.left,
+                Ordering::Equal => return Some(&node.item),
+            }
+        }
+        None
+    }
+
+    /// Returns the largest item less than or equal to `item`
+    pub fn floor<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut floor = None;
+        let mut index = self.root;
+        while index != NIL {
+            let node = self.node(index);
+            match self.comparator.compare(item, node.item.borrow()) {
+                Ordering::Greater => {
+                    floor = Some(&node.item);
+                    index = node.right;
+                }
+                Ordering::Less => index = node.left,
+                Ordering::Equal => return Some(&node.item),
+            }
+        }
+        floor
+    }
+
+    /// Returns the smallest item greater than or equal to `item`
+    pub fn ceiling<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut ceiling = None;
+        let mut index = self.root;
+        while index != NIL {
+            let node = self.node(index);
+            match self.comparator.compare(item, node.item.borrow()) {
+                Ordering::Greater => index = node.right,
+                Ordering::Less => {
+                    ceiling = Some(&node.item);
+                    index = node.left;
+                }
+                Ordering::Equal => return Some(&node.item),
+            }
+        }
+        ceiling
+    }
+
+    /// Returns the largest item strictly less than `item`
+    pub fn predecessor<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut predecessor = None;
+        let mut index = self.root;
+        while index != NIL {
+            let node = self.node(index);
+            if self.comparator.compare(item, node.item.borrow()) == Ordering::Greater {
+                predecessor = Some(&node.item);
+                index = node.right;
+            } else {
+                index = node.left;
+            }
+        }
+        predecessor
+    }
+
+    /// Returns the smallest item strictly greater than `item`
+    pub fn successor<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut successor = None;
+        let mut index = self.root;
+        while index != NIL {
+            let node = self.node(index);
+            if self.comparator.compare(item, node.item.borrow()) == Ordering::Less {
+                successor = Some(&node.item);
+                index = node.left;
+            } else {
+                index = node.right;
+            }
+        }
+        successor
+    }
+
+    /// Counts the items for which `in_prefix` holds, given that it holds for a prefix of the sorted items
+    fn count_prefix<F: Fn(&T) -> bool>(&self, in_prefix: F) -> usize {
+        let mut count = 0;
+        let mut index = self.root;
+        while index != NIL {
+            let node = self.node(index);
+            if in_prefix(&node.item) {
+                count += 1 + self.size(node.left) as usize;
+                index = node.right;
+            } else {
+                index = node.left;
+            }
+        }
+        count
+    }
+
+    /// Returns an iterator over the items inside `range`, in sorted order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::binary_search_tree::ArenaBinarySearchTree;
+    ///
+    /// let tree: ArenaBinarySearchTree<_> = vec![5, 2, 8, 1, 9, 3].into_iter().collect();
+    /// assert_eq!(tree.range(2..8).copied().collect::<Vec<_>>(), vec![2, 3, 5]);
+    /// assert_eq!(tree.range(4..).rev().copied().collect::<Vec<_>>(), vec![9, 8, 5]);
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> ArenaRange<'_, T, C>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let comparator = &self.comparator;
+        // Each stack holds the path to the next item to yield from its end
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        let mut index = self.root;
+        while index != NIL {
+            let node = self.node(index);
+            if above_start(comparator, node.item.borrow(), start) {
+                front.push(index);
+                index = node.left;
+            } else {
+                index = node.right;
+            }
+        }
+        let mut index = self.root;
+        while index != NIL {
+            let node = self.node(index);
+            if below_end(comparator, node.item.borrow(), end) {
+                back.push(index);
+                index = node.right;
+            } else {
+                index = node.left;
+            }
+        }
+        ArenaRange {
+            tree: self,
+            front,
+            back,
+            remaining: self.count_range((start, end)),
+        }
+    }
+
+    /// Counts the items inside `range`
+    pub fn count_range<Q, R>(&self, range: R) -> usize
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let comparator = &self.comparator;
+        let up_to_end = self.count_prefix(|item| below_end(comparator, item.borrow(), end));
+        let before_start = self.count_prefix(|item| !above_start(comparator, item.borrow(), start));
+        up_to_end.saturating_sub(before_start)
+    }
+
+    /// Removes every item inside `range`, returning the number of removed items
+    ///
+    /// Items are removed one at a time while the paths walked to them add up
+    /// to less than the size of the tree. The tree is then rebuilt to minimum
+    /// height without the remaining ones, in linear time.
+    pub fn remove_range<Q, R>(&mut self, range: R) -> usize
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let removed = self.count_range((start, end));
+        let comparator = &self.comparator;
+        let first = self.count_prefix(|item| !above_start(comparator, item.borrow(), start));
+        let mut budget = RebuildBudget::new(self.len());
+        for left in (1..=removed).rev() {
+            if !budget.spend(self.nth_path_length(first)) {
+                let mut items = self.take_sorted();
+                items.drain(first..first + left);
+                *self = Self::from_sorted_vec(items, self.comparator.clone());
+                break;
+            }
+            self.remove_nth(first);
+        }
+        removed
+    }
+
+    /// Moves all items greater than or equal to `item` into a new tree
+    ///
+    /// Both trees are rebuilt to minimum height, in linear time.
+    pub fn split_off<Q>(&mut self, item: &Q) -> Self
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let comparator = &self.comparator;
+        let at = self.count_prefix(|x| comparator.compare(x.borrow(), item) == Ordering::Less);
+        let mut low = self.take_sorted();
+        let high = low.split_off(at);
+        *self = Self::from_sorted_vec(low, self.comparator.clone());
+        Self::from_sorted_vec(high, self.comparator.clone())
+    }
+
+    /// Moves all items of `other` into this tree, leaving `other` empty
+    ///
+    /// Of two equal items, the one already in this tree is kept. Like
+    /// `extend`, a few items are inserted one at a time, while more are merged
+    /// in linear time. Both trees are assumed to use the same ordering.
+    pub fn append(&mut self, other: &mut Self) {
+        self.extend(other.take_sorted());
+    }
+
+    /// Returns an iterator over the items in sorted order
+    pub fn iter(&self) -> ArenaIter<'_, T, C> {
+        let mut iter = ArenaIter {
+            tree: self,
+            stack: Vec::new(),
+        };
+        iter.push_left_spine(self.root);
+        iter
+    }
+}
+
+impl<T, C: Compare<T> + Default> FromIterator<T> for ArenaBinarySearchTree<T, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+        Self::from_vec(items.into_iter().collect(), C::default())
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for ArenaBinarySearchTree<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        let items: Vec<T> = items.into_iter().collect();
+        let mut budget = RebuildBudget::new(self.len() + items.len());
+        let mut items = items.into_iter();
+        while let Some(item) = items.next() {
+            if !budget.spend(self.path_length(&item)) {
+                let mut added: Vec<T> = items.collect();
+                added.push(item);
+                let comparator = &self.comparator;
+                merge_sort_by(&mut added, |a, b| comparator.compare(a, b));
+                added.dedup_by(|a, b| comparator.compare(a, b) == Ordering::Equal);
+                let merged: Vec<T> = Merge {
+                    a: self.take_sorted().into_iter().peekable(),
+                    b: added.into_iter().peekable(),
+                    comparator: &self.comparator,
+                }
+                .collect();
+                *self = Self::from_sorted_vec(merged, self.comparator.clone());
+                return;
+            }
+            self.insert(item);
+        }
+    }
+}
+
+/// An in-order iterator over the items of an `ArenaBinarySearchTree`
+pub struct ArenaIter<'a, T, C: Compare<T> = Natural> {
+    tree: &'a ArenaBinarySearchTree<T, C>,
+    stack: Vec<u32>,
+}
+
+impl<'a, T, C: Compare<T>> ArenaIter<'a, T, C> {
+    fn push_left_spine(&mut self, mut index: u32) {
+        while index != NIL {
+            self.stack.push(index);
+            index = self.tree.node(index).left;
+        }
+    }
+}
+
+impl<'a, T, C: Compare<T>> Iterator for ArenaIter<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.tree.node(self.stack.pop()?);
+        self.push_left_spine(node.right);
+        Some(&node.item)
+    }
+}
+
+impl<'a, T, C: Compare<T>> FusedIterator for ArenaIter<'a, T, C> {}
+
+/// An iterator over the items of an `ArenaBinarySearchTree` that fall inside a range
+pub struct ArenaRange<'a, T, C: Compare<T> = Natural> {
+    tree: &'a ArenaBinarySearchTree<T, C>,
+    front: Vec<u32>,
+    back: Vec<u32>,
+    // Number of items left between the two ends
+    remaining: usize,
+}
+
+impl<'a, T, C: Compare<T>> Iterator for ArenaRange<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node = self.tree.node(self.front.pop().unwrap());
+        let mut index = node.right;
+        while index != NIL {
+            self.front.push(index);
+            index = self.tree.node(index).left;
+        }
+        Some(&node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, C: Compare<T>> DoubleEndedIterator for ArenaRange<'a, T, C> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node = self.tree.node(self.back.pop().unwrap());
+        let mut index = node.left;
+        while index != NIL {
+            self.back.push(index);
+            index = self.tree.node(index).right;
+        }
+        Some(&node.item)
+    }
+}
+
+impl<'a, T, C: Compare<T>> ExactSizeIterator for ArenaRange<'a, T, C> {}
+
+impl<'a, T, C: Compare<T>> FusedIterator for ArenaRange<'a, T, C> {}
+
+impl<'a, T, C: Compare<T>> IntoIterator for &'a ArenaBinarySearchTree<T, C> {
+    type Item = &'a T;
+    type IntoIter = ArenaIter<'a, T, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn tree(items: &[i32]) -> ArenaBinarySearchTree<i32> {
+        items.iter().copied().collect()
+    }
+
+    fn collect(tree: &ArenaBinarySearchTree<i32>) -> Vec<i32> {
+        tree.iter().copied().collect()
+    }
+
+    #[test]
+    fn empty_tree() {
+        let mut tree = tree(&[]);
+        assert!(tree.is_empty());
+        assert!(!tree.contains(&1));
+        assert_eq!(tree.remove(&1), None);
+        assert_eq!(tree.min(), None);
+        assert_eq!(tree.select(0), None);
+    }
+
+    #[test]
+    fn remove_nodes_with_zero_one_and_two_children() {
+        let items = [50, 20, 80, 10, 30, 25, 35, 70, 60, 75, 90];
+        let mut sorted = items.to_vec();
+        sorted.sort_unstable();
+        for item in &items {
+            let mut tree = tree(&items);
+            assert_eq!(tree.remove(item), Some(*item));
+            let expected: Vec<_> = sorted.iter().copied().filter(|i| i != item).collect();
+            assert_eq!(collect(&tree), expected);
+            for (k, item) in expected.iter().enumerate() {
+                assert_eq!(tree.select(k), Some(item));
+                assert_eq!(tree.rank(item), k);
+            }
+        }
+    }
+
+    #[test]
+    fn freed_slots_are_reused() {
+        let mut tree = tree(&(0..100).collect::<Vec<_>>());
+        for i in 0..50 {
+            tree.delete(i * 2);
+        }
+        assert_eq!(tree.len(), 50);
+        for i in 100..150 {
+            tree.insert(i);
+        }
+        assert_eq!(tree.slot_count(), 100);
+        assert_eq!(tree.len(), 100);
+
+        tree.clear();
+        assert!(tree.is_empty());
+        assert_eq!(tree.slot_count(), 0);
+        assert!(tree.capacity() >= 100);
+        tree.insert(1);
+        assert_eq!(collect(&tree), vec![1]);
+    }
+
+    #[test]
+    fn floor_and_ceiling() {
+        let tree = tree(&[10, 20, 30]);
+        assert_eq!(tree.floor(&25), Some(&20));
+        assert_eq!(tree.floor(&5), None);
+        assert_eq!(tree.ceiling(&25), Some(&30));
+        assert_eq!(tree.ceiling(&30), Some(&30));
+        assert_eq!(tree.ceiling(&31), None);
+        assert_eq!((tree.min(), tree.max()), (Some(&10), Some(&30)));
+    }
+
+    #[test]
+    fn lookups_by_borrowed_form() {
+        let mut tree = ArenaBinarySearchTree::new();
+        tree.insert(String::from("b"));
+        tree.insert(String::from("a"));
+        assert!(tree.contains("a"));
+        assert_eq!(tree.remove("b"), Some(String::from("b")));
+    }
+
+    #[test]
+    fn collecting_sorted_items_builds_balanced_tree() {
+        let tree: ArenaBinarySearchTree<_> = (0..1023).chain(0..1023).collect();
+        assert_eq!(tree.len(), 1023);
+        assert_eq!(tree.height(), 10);
+        assert_eq!(collect(&tree), (0..1023).collect::<Vec<_>>());
+        for k in 0..1023 {
+            assert_eq!(tree.select(k), Some(&(k as i32)));
+        }
+    }
+
+    #[test]
+    fn extend_merges_large_batches_and_inserts_small_ones() {
+        let mut tree = tree(&(0..1000).map(|i| i * 2).collect::<Vec<_>>());
+        tree.extend(1000..2000);
+        assert_eq!(tree.len(), 1500);
+        assert_eq!(tree.height(), 11);
+        assert_eq!(tree.slot_count(), 1500);
+
+        let root = tree.root;
+        tree.extend(vec![-1, -2, 5000]);
+        assert_eq!(tree.root, root);
+        assert_eq!(tree.len(), 1503);
+        assert_eq!((tree.min(), tree.max()), (Some(&-2), Some(&5000)));
+    }
+
+    #[test]
+    fn degenerate_trees_are_rebuilt_instead_of_walked() {
+        let mut tree = ArenaBinarySearchTree::new();
+        for i in 0..1000 {
+            tree.insert(i);
+        }
+        tree.extend(vec![1000, 1001, 1002]);
+        assert_eq!(tree.len(), 1003);
+        assert_eq!(tree.height(), 10);
+
+        let mut tree = ArenaBinarySearchTree::new();
+        for i in 0..1000 {
+            tree.insert(i);
+        }
+        assert_eq!(tree.remove_range(990..993), 3);
+        assert_eq!(tree.len(), 997);
+        assert_eq!(tree.height(), 10);
+        assert_eq!(
+            collect(&tree),
+            (0..990).chain(993..1000).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn predecessor_and_successor() {
+        let tree = tree(&[10, 20, 30]);
+        assert_eq!(tree.predecessor(&20), Some(&10));
+        assert_eq!(tree.predecessor(&25), Some(&20));
+        assert_eq!(tree.predecessor(&10), None);
+        assert_eq!(tree.successor(&20), Some(&30));
+        assert_eq!(tree.successor(&5), Some(&10));
+        assert_eq!(tree.successor(&30), None);
+    }
+
+    #[test]
+    fn pop_min_and_max() {
+        let mut tree = tree(&[3, 1, 4, 5, 2]);
+        assert_eq!(tree.pop_min(), Some(1));
+        assert_eq!(tree.pop_max(), Some(5));
+        assert_eq!(tree.pop_max(), Some(4));
+        assert_eq!(collect(&tree), vec![2, 3]);
+        assert_eq!(tree.pop_min(), Some(2));
+        assert_eq!(tree.pop_min(), Some(3));
+        assert_eq!((tree.pop_min(), tree.pop_max()), (None, None));
+    }
+
+    #[test]
+    fn range_matches_btree_set() {
+        use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+        let items: Vec<_> = (0..60).map(|i| i * 37 % 101).collect();
+        let tree = tree(&items);
+        let set: BTreeSet<_> = items.iter().copied().collect();
+        let bounds = |i: i32| -> Vec<Bound<i32>> { vec![Included(i), Excluded(i), Unbounded] };
+        for low in -1..102 {
+            for high in low..102 {
+                for &start in &bounds(low) {
+                    for &end in &bounds(high) {
+                        if let (Excluded(a), Excluded(b)) = (start, end) {
+                            if a == b {
+                                continue;
+                            }
+                        }
+                        let expected: Vec<_> = set.range((start, end)).copied().collect();
+                        let range = tree.range((start, end));
+                        assert_eq!(range.len(), expected.len());
+                        assert_eq!(range.copied().collect::<Vec<_>>(), expected);
+                        assert!(tree.range((start, end)).rev().eq(expected.iter().rev()));
+                        assert_eq!(tree.count_range((start, end)), expected.len());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn range_alternates_ends() {
+        let tree = tree(&(0..20).collect::<Vec<_>>());
+        let mut range = tree.range(5..=9);
+        assert_eq!(range.next(), Some(&5));
+        assert_eq!(range.next_back(), Some(&9));
+        assert_eq!(range.next(), Some(&6));
+        assert_eq!(range.next_back(), Some(&8));
+        assert_eq!(range.next(), Some(&7));
+        assert_eq!((range.next(), range.next_back()), (None, None));
+    }
+
+    #[test]
+    fn remove_range() {
+        let mut tree = tree(&(0..100).collect::<Vec<_>>());
+        assert_eq!(tree.remove_range(10..13), 3);
+        assert_eq!(tree.slot_count(), 100);
+        assert_eq!(tree.remove_range(50..), 50);
+        assert_eq!(tree.remove_range(200..300), 0);
+        let expected: Vec<_> = (0..10).chain(13..50).collect();
+        assert_eq!(collect(&tree), expected);
+        for (k, item) in expected.iter().enumerate() {
+            assert_eq!(tree.select(k), Some(item));
+        }
+        assert_eq!(tree.remove_range(..), 47);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn split_off_and_append() {
+        let mut low = tree(&(0..100).collect::<Vec<_>>());
+        let mut high = low.split_off(&60);
+        assert_eq!(collect(&low), (0..60).collect::<Vec<_>>());
+        assert_eq!(collect(&high), (60..100).collect::<Vec<_>>());
+        assert_eq!((low.height(), high.height()), (6, 6));
+        assert!(low.split_off(&1000).is_empty());
+
+        low.append(&mut high);
+        assert!(high.is_empty());
+        assert_eq!(collect(&low), (0..100).collect::<Vec<_>>());
+
+        let mut other = tree(&[-5, 50, 500]);
+        low.append(&mut other);
+        assert_eq!(low.len(), 102);
+        assert_eq!((low.min(), low.max()), (Some(&-5), Some(&500)));
+    }
+
+    #[test]
+    fn matches_btree_set() {
+        let mut tree = ArenaBinarySearchTree::new();
+        let mut set = BTreeSet::new();
+        let mut x: u64 = 1;
+        for _ in 0..5000 {
+            x = x.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let item = (x >> 33) % 500;
+            if x & (1 << 20) == 0 {
+                tree.insert(item);
+                set.insert(item);
+            } else {
+                assert_eq!(tree.remove(&item), set.take(&item));
+            }
+            assert_eq!(tree.len(), set.len());
+        }
+        assert!(tree.iter().eq(set.iter()));
+        assert!(tree.slot_count() <= 500);
+    }
+}
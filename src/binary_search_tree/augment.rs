@@ -0,0 +1,126 @@
+use std::cmp;
+use std::ops::Add;
+
+/// An aggregate kept for every subtree of a `BinarySearchTreeMap` or an `AvlTree`
+///
+/// The summaries of a subtree's entries are combined in key order, so
+/// `combine` must be associative and `identity` must be its neutral element.
+/// `combine` does not need to be commutative.
+///
+/// `BinarySearchTreeMap` is not rebalanced, so range aggregates take
+/// O(height) time, which is O(n) for keys inserted in sorted order. `AvlTree`
+/// is a set, which summarizes its items with `()` as the value, and recomputes
+/// the summaries in its rotations so that range aggregates take O(log n).
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::binary_search_tree::{Augment, BinarySearchTreeMap};
+///
+/// /// The longest value in a subtree
+/// struct LongestValue;
+///
+/// impl<K> Augment<K, String> for LongestValue {
+///     type Summary = usize;
+///
+///     fn identity() -> usize {
+///         0
+///     }
+///
+///     fn summarize(_key: &K, value: &String) -> usize {
+///         value.len()
+///     }
+///
+///     fn combine(left: &usize, right: &usize) -> usize {
+///         *left.max(right)
+///     }
+/// }
+///
+/// let mut map: BinarySearchTreeMap<i32, String, LongestValue> = BinarySearchTreeMap::default();
+/// map.insert(1, String::from("a"));
+/// map.insert(2, String::from("abc"));
+/// map.insert(3, String::from("ab"));
+///
+/// assert_eq!(map.aggregate(..), 3);
+/// assert_eq!(map.aggregate(3..), 2);
+/// ```
+pub trait Augment<K, V> {
+    type Summary: Clone;
+
+    /// The summary of an empty subtree
+    fn identity() -> Self::Summary;
+
+    /// The summary of a single entry
+    fn summarize(key: &K, value: &V) -> Self::Summary;
+
+    /// Combines the summaries of two adjacent runs of entries, `left` coming first
+    fn combine(left: &Self::Summary, right: &Self::Summary) -> Self::Summary;
+}
+
+/// No augmentation, used by plain maps
+impl<K, V> Augment<K, V> for () {
+    type Summary = ();
+
+    fn identity() {}
+
+    fn summarize(_key: &K, _value: &V) {}
+
+    fn combine(_left: &(), _right: &()) {}
+}
+
+/// Counts the entries of each subtree
+pub struct Count;
+
+impl<K, V> Augment<K, V> for Count {
+    type Summary = usize;
+
+    fn identity() -> usize {
+        0
+    }
+
+    fn summarize(_key: &K, _value: &V) -> usize {
+        1
+    }
+
+    fn combine(left: &usize, right: &usize) -> usize {
+        left + right
+    }
+}
+
+/// Sums the values of each subtree
+pub struct Sum;
+
+impl<K, V: Clone + Default + Add<Output = V>> Augment<K, V> for Sum {
+    type Summary = V;
+
+    fn identity() -> V {
+        V::default()
+    }
+
+    fn summarize(_key: &K, value: &V) -> V {
+        value.clone()
+    }
+
+    fn combine(left: &V, right: &V) -> V {
+        left.clone() + right.clone()
+    }
+}
+
+/// Keeps the largest value of each subtree
+pub struct MaxValue;
+
+impl<K, V: Ord + Clone> Augment<K, V> for MaxValue {
+    type Summary = Option<V>;
+
+    fn identity() -> Option<V> {
+        None
+    }
+
+    fn summarize(_key: &K, value: &V) -> Option<V> {
+        Some(value.clone())
+    }
+
+    fn combine(left: &Option<V>, right: &Option<V>) -> Option<V> {
+        cmp::max(left, right).clone()
+    }
+}
@@ -0,0 +1,246 @@
+use std::cmp::Ordering;
+
+/// An ordering of items, used by a `BinarySearchTree` in place of their `Ord` implementation
+///
+/// A tree keeps the ordering it was created with, so every subtree is
+/// searched with the same ordering the items were inserted with. Items that
+/// compare as `Equal` are considered the same item.
+///
+/// Orderings can be unit types such as `Natural`, or values carrying state,
+/// including any `Clone` closure taking two items. A tree stores its ordering
+/// once and searches every subtree with it by reference. The ordering is only
+/// cloned when a new tree is created from an existing one, such as by
+/// `split_off` or the set operators.
+///
+/// Lookups by a borrowed form `Q` of the items, such as `&str` for `String`
+/// items, are available when the ordering also implements `Compare<Q>`.
+///
+/// # Examples
+///
+/// Ordering strings by their length, which keeps one string of every length:
+///
+/// ```
+/// use algorithms::binary_search_tree::{BinarySearchTree, Compare};
+/// use std::cmp::Ordering;
+///
+/// #[derive(Clone, Default)]
+/// struct ByLength;
+///
+/// impl Compare<String> for ByLength {
+///     fn compare(&self, a: &String, b: &String) -> Ordering {
+///         a.len().cmp(&b.len())
+///     }
+/// }
+///
+/// let mut bst = BinarySearchTree::<String, ByLength>::default();
+/// for word in "the quick brown fox jumps over the lazy dog".split(' ') {
+///     bst.insert(word.to_string());
+/// }
+/// assert_eq!(bst.iter().collect::<Vec<_>>(), vec!["the", "over", "quick"]);
+/// ```
+///
+/// Ordering by a closure borrowing a table of priorities:
+///
+/// ```
+/// use algorithms::binary_search_tree::BinarySearchTree;
+/// use std::collections::HashMap;
+///
+/// let priority: HashMap<_, _> = vec![("low", 2), ("high", 0), ("medium", 1)].into_iter().collect();
+/// let mut bst = BinarySearchTree::with_comparator(|a: &&str, b: &&str| priority[a].cmp(&priority[b]));
+/// bst.extend(vec!["low", "high", "medium"]);
+/// assert_eq!(bst.iter().collect::<Vec<_>>(), vec![&"high", &"medium", &"low"]);
+/// ```
+pub trait Compare<T: ?Sized>: Clone {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+impl<T: ?Sized, F: Fn(&T, &T) -> Ordering + Clone> Compare<T> for F {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+/// Orders items by their `Ord` implementation
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Natural;
+
+impl<T: Ord + ?Sized> Compare<T> for Natural {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/// Reverses another ordering, the natural one by default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Reverse<C = Natural>(pub C);
+
+impl<T: ?Sized, C: Compare<T>> Compare<T> for Reverse<C> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self.0.compare(b, a)
+    }
+}
+
+/// Orders strings alphabetically, ignoring case
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CaseInsensitive;
+
+impl<T: AsRef<str> + ?Sized> Compare<T> for CaseInsensitive {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        let a = a.as_ref().chars().flat_map(char::to_lowercase);
+        let b = b.as_ref().chars().flat_map(char::to_lowercase);
+        a.cmp(b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_search_tree::BinarySearchTree;
+    use std::ops::Bound;
+
+    #[derive(Clone, Default)]
+    struct ByKey;
+
+    impl Compare<(i32, &str)> for ByKey {
+        fn compare(&self, a: &(i32, &str), b: &(i32, &str)) -> Ordering {
+            a.0.cmp(&b.0)
+        }
+    }
+
+    /// Orders numbers by their remainder modulo a divisor chosen at runtime
+    #[derive(Clone)]
+    struct Modulo(i32);
+
+    impl Compare<i32> for Modulo {
+        fn compare(&self, a: &i32, b: &i32) -> Ordering {
+            (a % self.0).cmp(&(b % self.0))
+        }
+    }
+
+    #[test]
+    fn natural_and_reverse() {
+        assert_eq!(Natural.compare(&1, &2), Ordering::Less);
+        assert_eq!(Reverse(Natural).compare(&1, &2), Ordering::Greater);
+        assert_eq!(Reverse(Reverse(Natural)).compare(&1, &2), Ordering::Less);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        assert_eq!(CaseInsensitive.compare("apple", "APPLE"), Ordering::Equal);
+        assert_eq!(CaseInsensitive.compare("Apple", "banana"), Ordering::Less);
+        assert_eq!(CaseInsensitive.compare("Ä", "ä"), Ordering::Equal);
+        assert_eq!(
+            CaseInsensitive.compare(&String::from("b"), &String::from("A")),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn closure_tree() {
+        let rank = [3, 0, 2, 1];
+        let mut bst =
+            BinarySearchTree::with_comparator(|a: &usize, b: &usize| rank[*a].cmp(&rank[*b]));
+        bst.extend(vec![0, 1, 2, 3]);
+        bst.delete(2);
+
+        assert_eq!(bst.iter().copied().collect::<Vec<_>>(), vec![1, 3, 0]);
+        assert!(bst.contains(&3));
+        assert!(!bst.contains(&2));
+        assert_eq!(bst.range(3..).copied().collect::<Vec<_>>(), vec![3, 0]);
+        assert_eq!(bst.validate(), Ok(()));
+
+        let reversed = Reverse(|a: &usize, b: &usize| rank[*a].cmp(&rank[*b]));
+        let mut bst = BinarySearchTree::with_comparator(reversed);
+        bst.extend(0..4);
+        assert_eq!(bst.iter().copied().collect::<Vec<_>>(), vec![0, 2, 3, 1]);
+    }
+
+    #[test]
+    fn stateful_comparator_tree() {
+        let mut by_three = BinarySearchTree::with_comparator(Modulo(3));
+        let mut by_five = BinarySearchTree::with_comparator(Modulo(5));
+        for i in 0..20 {
+            by_three.insert(i);
+            by_five.insert(i);
+        }
+        assert_eq!(by_three.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(by_five.len(), 5);
+        assert_eq!(by_five.remove(&13), Some(3));
+        assert_eq!(by_five.get(&24), Some(&4));
+
+        let high = by_five.split_off(&2);
+        assert_eq!(high.iter().copied().collect::<Vec<_>>(), vec![2, 4]);
+        assert_eq!(high.lower_bound(&8).current(), Some(&4));
+    }
+
+    #[test]
+    fn reverse_tree() {
+        let mut bst = BinarySearchTree::<i32, Reverse>::default();
+        for i in &[5, 2, 8, 1, 9, 3] {
+            bst.insert(*i);
+        }
+        bst.delete(9);
+
+        assert_eq!(bst.iter().copied().collect::<Vec<_>>(), vec![8, 5, 3, 2, 1]);
+        assert!(bst.contains(&2));
+        assert!(!bst.contains(&9));
+        assert_eq!(bst.min(), Some(&8));
+        assert_eq!(bst.rank(&3), 2);
+        assert_eq!(bst.floor(&4), Some(&5));
+        // Bounds follow the reversed order, so the range from 8 to 2 is not empty
+        let range = (Bound::Included(8), Bound::Excluded(2));
+        assert_eq!(bst.range(range).copied().collect::<Vec<_>>(), vec![8, 5, 3]);
+        assert_eq!(bst.count_range(..=3), 3);
+        assert_eq!(bst.lower_bound(&4).current(), Some(&3));
+        assert_eq!(bst.validate(), Ok(()));
+    }
+
+    #[test]
+    fn case_insensitive_tree() {
+        let mut bst: BinarySearchTree<String, CaseInsensitive> = vec!["banana", "Apple", "cherry"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        bst.insert(String::from("APPLE"));
+
+        assert_eq!(bst.len(), 3);
+        assert_eq!(bst.get("apple").map(String::as_str), Some("Apple"));
+        assert_eq!(bst.remove("BANANA").as_deref(), Some("banana"));
+        assert_eq!(bst.range(String::from("b")..String::from("D")).count(), 1);
+        assert_eq!(bst.validate(), Ok(()));
+    }
+
+    #[test]
+    fn projected_key_tree() {
+        let mut bst = BinarySearchTree::<_, ByKey>::default();
+        bst.insert((2, "two"));
+        bst.insert((1, "one"));
+        bst.insert((2, "deux"));
+        bst.insert((3, "three"));
+
+        assert_eq!(bst.get(&(2, "")), Some(&(2, "two")));
+        assert_eq!(bst.remove(&(1, "")), Some((1, "one")));
+
+        let mut high = bst.split_off(&(3, ""));
+        assert_eq!(bst.len(), 1);
+        bst.append(&mut high);
+        assert_eq!(
+            bst.iter().map(|item| item.1).collect::<Vec<_>>(),
+            vec!["two", "three"]
+        );
+    }
+
+    #[test]
+    fn set_operations_use_comparator() {
+        let a: BinarySearchTree<_, Reverse> = (0..6).collect();
+        let b: BinarySearchTree<_, Reverse> = (3..9).collect();
+        assert_eq!(
+            a.intersection(&b).copied().collect::<Vec<_>>(),
+            vec![5, 4, 3]
+        );
+        assert_eq!(
+            (&a ^ &b).iter().copied().collect::<Vec<_>>(),
+            vec![8, 7, 6, 2, 1, 0]
+        );
+    }
+}
@@ -0,0 +1,741 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+
+use super::{
+    delete_node, leftmost, rightmost, size, BinarySearchTree, Compare, Link, Natural, Node,
+};
+
+impl<T, C: Compare<T>> BinarySearchTree<T, C> {
+    /// Returns a cursor pointing at the smallest item
+    pub fn cursor_front(&self) -> Cursor<'_, T, C> {
+        let mut cursor = Cursor::ghost(self);
+        cursor.move_next();
+        cursor
+    }
+
+    /// Returns a cursor pointing at the largest item
+    pub fn cursor_back(&self) -> Cursor<'_, T, C> {
+        let mut cursor = Cursor::ghost(self);
+        cursor.move_prev();
+        cursor
+    }
+
+    /// Returns a cursor pointing at the smallest item greater than or equal to `item`
+    ///
+    /// If there is no such item, the cursor points past the last item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::binary_search_tree::BinarySearchTree;
+    ///
+    /// let bst: BinarySearchTree<_> = vec![10, 20, 30, 40].into_iter().collect();
+    /// let mut cursor = bst.lower_bound(&15);
+    /// assert_eq!(cursor.current(), Some(&20));
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current(), Some(&30));
+    /// cursor.move_prev();
+    /// cursor.move_prev();
+    /// assert_eq!(cursor.current(), Some(&10));
+    /// ```
+    pub fn lower_bound<Q>(&self, item: &Q) -> Cursor<'_, T, C>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut path = Vec::new();
+        // Length of the path above the smallest item seen so far that is not less than `item`
+        let mut bound = None;
+        let mut link = &self.root;
+        while let Some(ref node) = link {
+            match self.comparator.compare(item, node.item.borrow()) {
+                Ordering::Greater => {
+                    path.push((node.as_ref(), Ordering::Greater));
+                    link = &node.right;
+                }
+                Ordering::Less => {
+                    bound = Some(path.len());
+                    path.push((node.as_ref(), Ordering::Less));
+                    link = &node.left;
+                }
+                Ordering::Equal => {
+                    return Cursor {
+                        tree: self,
+                        path,
+                        current: Some(node.as_ref()),
+                    };
+                }
+            }
+        }
+        match bound {
+            Some(len) => {
+                path.truncate(len + 1);
+                let (node, _) = path.pop().unwrap();
+                Cursor {
+                    tree: self,
+                    path,
+                    current: Some(node),
+                }
+            }
+            None => Cursor::ghost(self),
+        }
+    }
+
+    /// Returns a mutable cursor pointing at the smallest item
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T, C> {
+        let mut cursor = CursorMut::ghost(self);
+        cursor.move_next();
+        cursor
+    }
+
+    /// Returns a mutable cursor pointing at the largest item
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T, C> {
+        let mut cursor = CursorMut::ghost(self);
+        cursor.move_prev();
+        cursor
+    }
+
+    /// Returns a mutable cursor pointing at the smallest item greater than or equal to `item`
+    ///
+    /// If there is no such item, the cursor points past the last item.
+    pub fn lower_bound_mut<Q>(&mut self, item: &Q) -> CursorMut<'_, T, C>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        let mut cursor = CursorMut::ghost(self);
+        let mut bound = None;
+        loop {
+            let ordering = match cursor.focus {
+                Some(ref node) => cursor.tree.comparator.compare(item, node.item.borrow()),
+                None => break,
+            };
+            match ordering {
+                Ordering::Greater => cursor.descend(Ordering::Greater),
+                Ordering::Less => {
+                    bound = Some(cursor.path.len());
+                    cursor.descend(Ordering::Less);
+                }
+                Ordering::Equal => {
+                    cursor.ghost = false;
+                    return cursor;
+                }
+            }
+        }
+        match bound {
+            Some(len) => {
+                while cursor.path.len() > len {
+                    cursor.ascend();
+                }
+                cursor.ghost = false;
+            }
+            None => cursor.zip_up(),
+        }
+        cursor
+    }
+}
+
+/// Adds an item smaller than all items of a subtree
+fn push_min<T>(mut link: &mut Link<T>, item: T) {
+    while let Some(ref mut node) = link {
+        node.size += 1;
+        link = &mut node.left;
+    }
+    *link = Some(Box::new(Node::new(item)));
+}
+
+/// Adds an item larger than all items of a subtree
+fn push_max<T>(mut link: &mut Link<T>, item: T) {
+    while let Some(ref mut node) = link {
+        node.size += 1;
+        link = &mut node.right;
+    }
+    *link = Some(Box::new(Node::new(item)));
+}
+
+/// A cursor over the items of a `BinarySearchTree`, which can move in both directions
+///
+/// The cursor points either at an item or at a position past the last item,
+/// which also acts as the position before the first item. Moving past either
+/// end reaches that position, and moving again wraps around to the other end.
+///
+/// The cursor keeps the path from the root to the current item, so moving to
+/// a neighbouring item takes O(1) amortized time instead of a new search.
+pub struct Cursor<'a, T, C: Compare<T> = Natural> {
+    tree: &'a BinarySearchTree<T, C>,
+    // Ancestors of the current node, along with the direction taken from each of them
+    path: Vec<(&'a Node<T>, Ordering)>,
+    current: Option<&'a Node<T>>,
+}
+
+impl<'a, T, C: Compare<T>> Clone for Cursor<'a, T, C> {
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree,
+            path: self.path.clone(),
+            current: self.current,
+        }
+    }
+}
+
+impl<'a, T, C: Compare<T>> Cursor<'a, T, C> {
+    fn ghost(tree: &'a BinarySearchTree<T, C>) -> Self {
+        Self {
+            tree,
+            path: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// Returns the item the cursor points at, or `None` if it points past the last item
+    pub fn current(&self) -> Option<&'a T> {
+        self.current.map(|node| &node.item)
+    }
+
+    /// Moves to the next item, or past the last item
+    pub fn move_next(&mut self) {
+        let tree = self.tree;
+        match self.current {
+            None => self.descend_to_extreme(&tree.root, Ordering::Less),
+            Some(node) if node.right.is_some() => {
+                self.path.push((node, Ordering::Greater));
+                self.descend_to_extreme(&node.right, Ordering::Less);
+            }
+            Some(_) => self.ascend_from(Ordering::Less),
+        }
+    }
+
+    /// Moves to the previous item, or past the last item
+    pub fn move_prev(&mut self) {
+        let tree = self.tree;
+        match self.current {
+            None => self.descend_to_extreme(&tree.root, Ordering::Greater),
+            Some(node) if node.left.is_some() => {
+                self.path.push((node, Ordering::Less));
+                self.descend_to_extreme(&node.left, Ordering::Greater);
+            }
+            Some(_) => self.ascend_from(Ordering::Greater),
+        }
+    }
+
+    /// Walks down to the smallest item of a subtree for `Less`, or the largest for `Greater`
+    fn descend_to_extreme(&mut self, link: &'a Link<T>, direction: Ordering) {
+        let mut node = match link {
+            Some(node) => node.as_ref(),
+            None => return,
+        };
+        loop {
+            let child = match direction {
+                Ordering::Less => &node.left,
+                _ => &node.right,
+            };
+            match child {
+                Some(ref child) => {
+                    self.path.push((node, direction));
+                    node = child;
+                }
+                None => break,
+            }
+        }
+        self.current = Some(node);
+    }
+
+    /// Walks up to the first ancestor that was left in `direction`
+    fn ascend_from(&mut self, direction: Ordering) {
+        self.current = None;
+        while let Some((node, taken)) = self.path.pop() {
+            if taken == direction {
+                self.current = Some(node);
+                return;
+            }
+        }
+    }
+}
+
+/// A cursor over the items of a `BinarySearchTree` that can add and remove items
+///
+/// Works like `Cursor`, but can also remove the current item and insert
+/// items next to it without searching from the root. While the cursor exists
+/// the tree is unzipped along the path to the current item: the nodes above it
+/// are detached from their child on the path, and are put back together as the
+/// cursor moves up or is dropped. Subtree sizes are updated as the nodes are
+/// put back.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::binary_search_tree::BinarySearchTree;
+///
+/// let mut bst: BinarySearchTree<_> = (1..=10).collect();
+/// let mut cursor = bst.cursor_front_mut();
+/// while let Some(&item) = cursor.current() {
+///     if item % 3 == 0 {
+///         cursor.remove_current();
+///     } else {
+///         cursor.move_next();
+///     }
+/// }
+/// drop(cursor);
+/// assert_eq!(bst.iter().copied().collect::<Vec<_>>(), vec![1, 2, 4, 5, 7, 8, 10]);
+/// ```
+pub struct CursorMut<'a, T, C: Compare<T> = Natural> {
+    tree: &'a mut BinarySearchTree<T, C>,
+    // Ancestors of `focus`, each with its child in the given direction taken out
+    path: Vec<(Box<Node<T>>, Ordering)>,
+    // The subtree rooted at the current item, or the whole tree when the cursor points past the last item
+    focus: Link<T>,
+    ghost: bool,
+}
+
+impl<'a, T, C: Compare<T>> CursorMut<'a, T, C> {
+    fn ghost(tree: &'a mut BinarySearchTree<T, C>) -> Self {
+        let focus = tree.root.take();
+        Self {
+            tree,
+            path: Vec::new(),
+            focus,
+            ghost: true,
+        }
+    }
+
+    /// Returns the item the cursor points at, or `None` if it points past the last item
+    pub fn current(&self) -> Option<&T> {
+        if self.ghost {
+            return None;
+        }
+        self.focus.as_ref().map(|node| &node.item)
+    }
+
+    /// Moves to the next item, or past the last item
+    pub fn move_next(&mut self) {
+        if self.ghost {
+            if self.focus.is_some() {
+                self.ghost = false;
+                self.descend_to_extreme(Ordering::Less);
+            }
+        } else if self.focus.as_ref().unwrap().right.is_some() {
+            self.descend(Ordering::Greater);
+            self.descend_to_extreme(Ordering::Less);
+        } else {
+            self.ascend_from(Ordering::Less);
+        }
+    }
+
+    /// Moves to the previous item, or past the last item
+    pub fn move_prev(&mut self) {
+        if self.ghost {
+            if self.focus.is_some() {
+                self.ghost = false;
+                self.descend_to_extreme(Ordering::Greater);
+            }
+        } else if self.focus.as_ref().unwrap().left.is_some() {
+            self.descend(Ordering::Less);
+            self.descend_to_extreme(Ordering::Greater);
+        } else {
+            self.ascend_from(Ordering::Greater);
+        }
+    }
+
+    /// Removes the current item and moves to the next one
+    ///
+    /// Returns `None` without changing the tree if the cursor points past the last item.
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.ghost {
+            return None;
+        }
+        let node = self.focus.as_ref().unwrap();
+        let has_left = node.left.is_some();
+        let has_right = node.right.is_some();
+        // With two children the successor takes the place of the removed item in the same node
+        let item = delete_node(&mut self.focus);
+        match (has_left, has_right) {
+            (true, true) => {}
+            (false, true) => self.descend_to_extreme(Ordering::Less),
+            _ => self.ascend_from(Ordering::Less),
+        }
+        Some(item)
+    }
+
+    /// Inserts `item` right after the current item, without moving the cursor
+    ///
+    /// If the cursor points past the last item, `item` is inserted first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item` is not between the current item and the next one.
+    pub fn insert_after(&mut self, item: T) {
+        let comparator = &self.tree.comparator;
+        if self.ghost {
+            assert!(
+                leftmost(&self.focus).map_or(true, |first| comparator.compare(&item, first)
+                    == Ordering::Less),
+                "Item does not belong after the cursor"
+            );
+            push_min(&mut self.focus, item);
+            return;
+        }
+        let node = self.focus.as_mut().unwrap();
+        let next = match leftmost(&node.right) {
+            Some(next) => Some(next),
+            None => self
+                .path
+                .iter()
+                .rev()
+                .find(|(_, direction)| *direction == Ordering::Less)
+                .map(|(node, _)| &node.item),
+        };
+        assert!(
+            comparator.compare(&item, &node.item) == Ordering::Greater
+                && next.map_or(true, |next| comparator.compare(&item, next)
+                    == Ordering::Less),
+            "Item does not belong after the cursor"
+        );
+        node.size += 1;
+        push_min(&mut node.right, item);
+    }
+
+    /// Inserts `item` right before the current item, without moving the cursor
+    ///
+    /// If the cursor points past the last item, `item` is inserted last.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item` is not between the previous item and the current one.
+    pub fn insert_before(&mut self, item: T) {
+        let comparator = &self.tree.comparator;
+        if self.ghost {
+            assert!(
+                rightmost(&self.focus).map_or(true, |last| comparator.compare(&item, last)
+                    == Ordering::Greater),
+                "Item does not belong before the cursor"
+            );
+            push_max(&mut self.focus, item);
+            return;
+        }
+        let node = self.focus.as_mut().unwrap();
+        let prev = match rightmost(&node.left) {
+            Some(prev) => Some(prev),
+            None => self
+                .path
+                .iter()
+                .rev()
+                .find(|(_, direction)| *direction == Ordering::Greater)
+                .map(|(node, _)| &node.item),
+        };
+        assert!(
+            comparator.compare(&item, &node.item) == Ordering::Less
+                && prev.map_or(true, |prev| comparator.compare(&item, prev)
+                    == Ordering::Greater),
+            "Item does not belong before the cursor"
+        );
+        node.size += 1;
+        push_max(&mut node.left, item);
+    }
+
+    /// Makes the child of the current node in `direction` the new focus
+    fn descend(&mut self, direction: Ordering) {
+        let mut node = self.focus.take().unwrap();
+        self.focus = match direction {
+            Ordering::Less => node.left.take(),
+            _ => node.right.take(),
+        };
+        self.path.push((node, direction));
+    }
+
+    /// Puts the focus back into its parent, which becomes the new focus
+    fn ascend(&mut self) -> Option<Ordering> {
+        let (mut node, direction) = self.path.pop()?;
+        let child = self.focus.take();
+        node.size = 1 + size(&node.left) + size(&node.right) + size(&child);
+        match direction {
+            Ordering::Less => node.left = child,
+            _ => node.right = child,
+        }
+        self.focus = Some(node);
+        Some(direction)
+    }
+
+    /// Walks down to the smallest item of the focus for `Less`, or the largest for `Greater`
+    fn descend_to_extreme(&mut self, direction: Ordering) {
+        loop {
+            let node = self.focus.as_ref().unwrap();
+            let child = match direction {
+                Ordering::Less => &node.left,
+                _ => &node.right,
+            };
+            if child.is_none() {
+                return;
+            }
+            self.descend(direction);
+        }
+    }
+
+    /// Walks up to the first ancestor that was left in `direction`
+    fn ascend_from(&mut self, direction: Ordering) {
+        while let Some(taken) = self.ascend() {
+            if taken == direction {
+                return;
+            }
+        }
+        self.ghost = true;
+    }
+
+    /// Puts the whole tree back together and moves past the last item
+    fn zip_up(&mut self) {
+        while self.ascend().is_some() {}
+        self.ghost = true;
+    }
+}
+
+impl<'a, T, C: Compare<T>> Drop for CursorMut<'a, T, C> {
+    fn drop(&mut self) {
+        self.zip_up();
+        self.tree.root = self.focus.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(items: &[i32]) -> BinarySearchTree<i32> {
+        let mut bst = BinarySearchTree::new();
+        for item in items {
+            bst.insert(*item);
+        }
+        bst
+    }
+
+    fn collect(bst: &BinarySearchTree<i32>) -> Vec<i32> {
+        bst.iter().copied().collect()
+    }
+
+    const ITEMS: [i32; 11] = [50, 20, 80, 10, 30, 25, 35, 70, 60, 75, 90];
+
+    #[test]
+    fn cursor_on_empty_tree() {
+        let mut bst = tree(&[]);
+        let mut cursor = bst.cursor_front();
+        assert_eq!(cursor.current(), None);
+        cursor.move_next();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(bst.lower_bound(&1).current(), None);
+
+        let mut cursor = bst.cursor_back_mut();
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+    }
+
+    #[test]
+    fn cursor_walks_in_both_directions() {
+        let bst = tree(&ITEMS);
+        let mut sorted = ITEMS.to_vec();
+        sorted.sort_unstable();
+
+        let mut cursor = bst.cursor_front();
+        let mut forward = Vec::new();
+        while let Some(item) = cursor.current() {
+            forward.push(*item);
+            cursor.move_next();
+        }
+        assert_eq!(forward, sorted);
+
+        let mut cursor = bst.cursor_back();
+        let mut backward = Vec::new();
+        while let Some(item) = cursor.current() {
+            backward.push(*item);
+            cursor.move_prev();
+        }
+        sorted.reverse();
+        assert_eq!(backward, sorted);
+    }
+
+    #[test]
+    fn cursor_wraps_around_past_the_ends() {
+        let bst = tree(&ITEMS);
+        let mut cursor = bst.cursor_back();
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&10));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&90));
+    }
+
+    #[test]
+    fn lower_bound_finds_item_or_next_one() {
+        let bst = tree(&ITEMS);
+        for key in 0..100 {
+            let expected = ITEMS.iter().filter(|item| **item >= key).min();
+            assert_eq!(bst.lower_bound(&key).current(), expected);
+
+            let mut cursor = bst.lower_bound(&key);
+            cursor.move_prev();
+            let before = ITEMS.iter().filter(|item| **item < key).max();
+            assert_eq!(cursor.current(), before);
+        }
+    }
+
+    #[test]
+    fn lower_bound_mut_finds_item_or_next_one() {
+        let mut bst = tree(&ITEMS);
+        for key in 0..100 {
+            let expected = ITEMS.iter().filter(|item| **item >= key).min();
+            let mut cursor = bst.lower_bound_mut(&key);
+            assert_eq!(cursor.current(), expected);
+            cursor.move_next();
+            // Past the last item the cursor wraps around to the first one
+            let after = match expected {
+                Some(bound) => ITEMS.iter().filter(|item| *item > bound).min(),
+                None => ITEMS.iter().min(),
+            };
+            assert_eq!(cursor.current(), after);
+        }
+        assert_eq!(collect(&bst).len(), ITEMS.len());
+        assert_eq!(bst.validate(), Ok(()));
+    }
+
+    #[test]
+    fn cursor_mut_walks_in_both_directions() {
+        let mut bst = tree(&ITEMS);
+        let mut sorted = ITEMS.to_vec();
+        sorted.sort_unstable();
+
+        let mut cursor = bst.cursor_front_mut();
+        for item in &sorted {
+            assert_eq!(cursor.current(), Some(item));
+            cursor.move_next();
+        }
+        assert_eq!(cursor.current(), None);
+        for item in sorted.iter().rev() {
+            cursor.move_prev();
+            assert_eq!(cursor.current(), Some(item));
+        }
+        drop(cursor);
+        assert_eq!(collect(&bst), sorted);
+        assert_eq!(bst.validate(), Ok(()));
+    }
+
+    #[test]
+    fn remove_current_moves_to_next_item() {
+        let mut sorted = ITEMS.to_vec();
+        sorted.sort_unstable();
+        for (i, item) in sorted.iter().enumerate() {
+            let mut bst = tree(&ITEMS);
+            let mut cursor = bst.lower_bound_mut(item);
+            assert_eq!(cursor.remove_current(), Some(*item));
+            assert_eq!(cursor.current(), sorted.get(i + 1));
+            drop(cursor);
+
+            let expected: Vec<_> = sorted.iter().copied().filter(|x| x != item).collect();
+            assert_eq!(collect(&bst), expected);
+            assert_eq!(bst.len(), ITEMS.len() - 1);
+            assert_eq!(bst.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn remove_every_item_with_one_cursor() {
+        let mut bst = tree(&ITEMS);
+        let mut cursor = bst.cursor_front_mut();
+        let mut removed = Vec::new();
+        while let Some(item) = cursor.remove_current() {
+            removed.push(item);
+        }
+        drop(cursor);
+        assert!(removed.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(removed.len(), ITEMS.len());
+        assert!(bst.is_empty());
+    }
+
+    #[test]
+    fn insert_next_to_current_item() {
+        let mut bst = tree(&[10, 20, 30]);
+        let mut cursor = bst.lower_bound_mut(&20);
+        cursor.insert_after(25);
+        cursor.insert_after(21);
+        cursor.insert_before(15);
+        cursor.insert_before(19);
+        assert_eq!(cursor.current(), Some(&20));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&21));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&19));
+        drop(cursor);
+
+        assert_eq!(collect(&bst), vec![10, 15, 19, 20, 21, 25, 30]);
+        assert_eq!(bst.validate(), Ok(()));
+    }
+
+    #[test]
+    fn insert_at_the_ends() {
+        let mut bst = tree(&[]);
+        let mut cursor = bst.cursor_front_mut();
+        cursor.insert_before(2);
+        cursor.insert_before(3);
+        cursor.insert_after(1);
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&1));
+        cursor.insert_before(0);
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&3));
+        cursor.insert_after(4);
+        drop(cursor);
+
+        assert_eq!(collect(&bst), vec![0, 1, 2, 3, 4]);
+        assert_eq!(bst.validate(), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Item does not belong after the cursor")]
+    fn insert_after_out_of_order_panics() {
+        let mut bst = tree(&[10, 20, 30]);
+        let mut cursor = bst.lower_bound_mut(&10);
+        cursor.insert_after(25);
+    }
+
+    #[test]
+    #[should_panic(expected = "Item does not belong before the cursor")]
+    fn insert_before_out_of_order_panics() {
+        let mut bst = tree(&[10, 20, 30]);
+        let mut cursor = bst.lower_bound_mut(&30);
+        cursor.insert_before(20);
+    }
+
+    #[test]
+    fn sweep_keeps_tree_consistent() {
+        // Replace every run of consecutive numbers with its endpoints
+        let mut bst: BinarySearchTree<i32> = (0..200).filter(|i| i % 7 != 3).collect();
+        let mut cursor = bst.cursor_front_mut();
+        let mut previous = None;
+        while let Some(&item) = cursor.current() {
+            cursor.move_next();
+            let next = cursor.current().copied();
+            cursor.move_prev();
+            if previous == Some(item - 1) && next == Some(item + 1) {
+                cursor.remove_current();
+            } else {
+                cursor.move_next();
+            }
+            previous = Some(item);
+        }
+        drop(cursor);
+
+        let mut expected = Vec::new();
+        for i in 0..200 {
+            if i % 7 != 3 && (i == 0 || i == 199 || (i - 1) % 7 == 3 || (i + 1) % 7 == 3) {
+                expected.push(i);
+            }
+        }
+        assert_eq!(collect(&bst), expected);
+        assert_eq!(bst.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,221 @@
+//! Randomized differential testing of `BinarySearchTree` against `BTreeSet`
+//!
+//! Random sequences of operations are applied to both sets, and every
+//! observable result is compared after each operation. A failing sequence is
+//! shrunk before being reported, by removing operations and lowering keys for
+//! as long as it keeps failing.
+
+use super::BinarySearchTree;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeSet;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Insert(i32),
+    Delete(i32),
+    Remove(i32),
+    Contains(i32),
+}
+
+impl Op {
+    fn key(self) -> i32 {
+        match self {
+            Op::Insert(key) | Op::Delete(key) | Op::Remove(key) | Op::Contains(key) => key,
+        }
+    }
+
+    fn with_key(self, key: i32) -> Self {
+        match self {
+            Op::Insert(_) => Op::Insert(key),
+            Op::Delete(_) => Op::Delete(key),
+            Op::Remove(_) => Op::Remove(key),
+            Op::Contains(_) => Op::Contains(key),
+        }
+    }
+}
+
+/// Generates a sequence of operations on keys below a random bound, so that small bounds give many repeated keys
+fn generate(rng: &mut StdRng, len: usize) -> Vec<Op> {
+    let keys = rng.gen_range(1, 1000);
+    (0..len)
+        .map(|_| {
+            let key = rng.gen_range(0, keys);
+            match rng.gen_range(0, 8) {
+                0..=3 => Op::Insert(key),
+                4 => Op::Delete(key),
+                5 => Op::Remove(key),
+                _ => Op::Contains(key),
+            }
+        })
+        .collect()
+}
+
+/// Applies operations to a `BinarySearchTree` and a `BTreeSet`, describing the first difference between them
+fn run(ops: &[Op]) -> Result<(), String> {
+    let mut bst = BinarySearchTree::new();
+    let mut expected = BTreeSet::new();
+    for (step, &op) in ops.iter().enumerate() {
+        let fail = |what: String| Err(format!("After step {} ({:?}): {}", step, op, what));
+        match op {
+            Op::Insert(key) => {
+                bst.insert(key);
+                expected.insert(key);
+            }
+            Op::Delete(key) => {
+                bst.delete(key);
+                expected.remove(&key);
+            }
+            Op::Remove(key) => {
+                let (actual, wanted) = (bst.remove(&key), expected.take(&key));
+                if actual != wanted {
+                    return fail(format!("removed {:?}, expected {:?}", actual, wanted));
+                }
+            }
+            Op::Contains(key) => {
+                let (actual, wanted) = (bst.contains(&key), expected.contains(&key));
+                if actual != wanted {
+                    return fail(format!("contains gave {}, expected {}", actual, wanted));
+                }
+            }
+        }
+        if bst.len() != expected.len() {
+            return fail(format!("len is {}, expected {}", bst.len(), expected.len()));
+        }
+        if bst.min() != expected.iter().next() || bst.max() != expected.iter().next_back() {
+            return fail(format!(
+                "min and max are {:?} and {:?}, expected {:?} and {:?}",
+                bst.min(),
+                bst.max(),
+                expected.iter().next(),
+                expected.iter().next_back()
+            ));
+        }
+        if !bst.iter().eq(expected.iter()) {
+            return fail(format!(
+                "items are {:?}, expected {:?}",
+                bst.iter().collect::<Vec<_>>(),
+                expected
+            ));
+        }
+        if let Err(error) = bst.validate() {
+            return fail(format!("tree is invalid: {:?}", error));
+        }
+    }
+    Ok(())
+}
+
+/// Shrinks a sequence of operations for which `property` fails, keeping it failing
+///
+/// First tries removing runs of operations, halving their length down to
+/// single operations, then tries lowering each key towards zero. Repeats
+/// until neither makes progress.
+fn shrink<P: Fn(&[Op]) -> Result<(), String>>(mut ops: Vec<Op>, property: P) -> Vec<Op> {
+    let fails = |ops: &[Op]| property(ops).is_err();
+    loop {
+        let mut progress = false;
+
+        let mut chunk = (ops.len() + 1) / 2;
+        while chunk > 0 {
+            let mut start = 0;
+            while start < ops.len() {
+                let end = (start + chunk).min(ops.len());
+                let candidate: Vec<_> = ops[..start].iter().chain(&ops[end..]).copied().collect();
+                if fails(&candidate) {
+                    ops = candidate;
+                    progress = true;
+                } else {
+                    start += chunk;
+                }
+            }
+            chunk /= 2;
+        }
+
+        for i in 0..ops.len() {
+            let key = ops[i].key();
+            if key <= 0 {
+                continue;
+            }
+            for smaller in [0, key / 2, key - 1] {
+                let mut candidate = ops.clone();
+                candidate[i] = ops[i].with_key(smaller);
+                if fails(&candidate) {
+                    ops = candidate;
+                    progress = true;
+                    break;
+                }
+            }
+        }
+
+        if !progress {
+            return ops;
+        }
+    }
+}
+
+/// Runs `property` on random sequences of operations, panicking with a shrunk sequence if one fails
+fn check<P: Fn(&[Op]) -> Result<(), String>>(cases: u64, max_len: usize, property: P) {
+    for seed in 0..cases {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let len = rng.gen_range(0, max_len + 1);
+        let ops = generate(&mut rng, len);
+        if property(&ops).is_err() {
+            let minimal = shrink(ops, &property);
+            let error = property(&minimal).unwrap_err();
+            panic!("Seed {} failed, shrunk to {:?}: {}", seed, minimal, error);
+        }
+    }
+}
+
+#[test]
+fn matches_btree_set() {
+    check(300, 500, run);
+}
+
+#[test]
+fn shrinks_to_minimal_sequence() {
+    // Fails whenever three distinct keys are present at once
+    let property = |ops: &[Op]| -> Result<(), String> {
+        let mut set = BTreeSet::new();
+        for &op in ops {
+            match op {
+                Op::Insert(key) => {
+                    set.insert(key);
+                }
+                Op::Delete(key) | Op::Remove(key) => {
+                    set.remove(&key);
+                }
+                Op::Contains(_) => {}
+            }
+            if set.len() >= 3 {
+                return Err(String::from("Three keys"));
+            }
+        }
+        Ok(())
+    };
+    let mut rng = StdRng::seed_from_u64(7);
+    let ops = generate(&mut rng, 200);
+    assert!(property(&ops).is_err());
+
+    let mut minimal = shrink(ops, property);
+    assert!(minimal.iter().all(|op| matches!(op, Op::Insert(_))));
+    minimal.sort_by_key(|op| op.key());
+    assert_eq!(minimal, vec![Op::Insert(0), Op::Insert(1), Op::Insert(2)]);
+}
+
+#[test]
+#[should_panic(expected = "shrunk to [Insert(0), Contains(0)]")]
+fn reports_shrunk_failure() {
+    // Pretends that inserted keys are never found
+    check(10, 100, |ops| {
+        let mut inserted = false;
+        for &op in ops {
+            match op {
+                Op::Insert(_) => inserted = true,
+                Op::Contains(_) if inserted => return Err(String::from("Not found")),
+                _ => {}
+            }
+        }
+        Ok(())
+    });
+}
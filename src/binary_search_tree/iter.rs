@@ -0,0 +1,480 @@
+use std::collections::VecDeque;
+use std::iter::FusedIterator;
+
+use super::{free, BinarySearchTree, Compare, Link, Node};
+
+pub(super) enum Pending<I, S> {
+    Item(I),
+    Tree(S),
+}
+
+/// Lazy in-order traversal shared by the borrowing and owning iterators
+///
+/// Keeps a deque of items and unexpanded subtrees in sorted order. Subtrees are
+/// expanded from whichever end is consumed, so each node is visited once and the
+/// deque never holds more than O(height) entries per end.
+pub(super) struct InOrder<I, S> {
+    pending: VecDeque<Pending<I, S>>,
+}
+
+impl<I, S> InOrder<I, S> {
+    fn new(tree: S) -> Self {
+        let mut pending = VecDeque::new();
+        pending.push_back(Pending::Tree(tree));
+        Self { pending }
+    }
+
+    /// Creates a traversal over items and subtrees that are already in sorted order
+    pub(super) fn from_pending(pending: VecDeque<Pending<I, S>>) -> Self {
+        Self { pending }
+    }
+
+    pub(super) fn next(&mut self, split: impl Fn(S) -> Option<(S, I, S)>) -> Option<I> {
+        while let Some(pending) = self.pending.pop_front() {
+            match pending {
+                Pending::Item(item) => return Some(item),
+                Pending::Tree(tree) => {
+                    if let Some((left, item, right)) = split(tree) {
+                        self.pending.push_front(Pending::Tree(right));
+                        self.pending.push_front(Pending::Item(item));
+                        self.pending.push_front(Pending::Tree(left));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub(super) fn next_back(&mut self, split: impl Fn(S) -> Option<(S, I, S)>) -> Option<I> {
+        while let Some(pending) = self.pending.pop_back() {
+            match pending {
+                Pending::Item(item) => return Some(item),
+                Pending::Tree(tree) => {
+                    if let Some((left, item, right)) = split(tree) {
+                        self.pending.push_back(Pending::Tree(left));
+                        self.pending.push_back(Pending::Item(item));
+                        self.pending.push_back(Pending::Tree(right));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// The left subtree, item and right subtree of a non-empty tree
+type Split<S, I> = Option<(S, I, S)>;
+
+pub(super) fn split_ref<T>(link: &Link<T>) -> Split<&Link<T>, &T> {
+    link.as_ref()
+        .map(|node| (&node.left, &node.item, &node.right))
+}
+
+fn split_mut<T>(link: &mut Link<T>) -> Split<&mut Link<T>, &mut T> {
+    link.as_mut().map(|node| {
+        let Node {
+            item, left, right, ..
+        } = &mut **node;
+        (left, item, right)
+    })
+}
+
+fn split_owned<T>(link: Link<T>) -> Split<Link<T>, T> {
+    link.map(|node| {
+        let Node {
+            item, left, right, ..
+        } = *node;
+        (left, item, right)
+    })
+}
+
+/// An in-order iterator over the items of a `BinarySearchTree`
+pub struct Iter<'a, T> {
+    inner: InOrder<&'a T, &'a Link<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next(split_ref)
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back(split_ref)
+    }
+}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+/// A mutable in-order iterator over the items of a `BinarySearchTree`
+pub struct IterMut<'a, T> {
+    inner: InOrder<&'a mut T, &'a mut Link<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next(split_mut)
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back(split_mut)
+    }
+}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+/// An owning in-order iterator over the items of a `BinarySearchTree`
+pub struct IntoIter<T> {
+    inner: InOrder<T, Link<T>>,
+}
+
+impl<T> IntoIter<T> {
+    pub(super) fn new(link: Link<T>) -> Self {
+        Self {
+            inner: InOrder::new(link),
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next(split_owned)
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back(split_owned)
+    }
+}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> Drop for IntoIter<T> {
+    fn drop(&mut self) {
+        for pending in self.inner.pending.drain(..) {
+            if let Pending::Tree(link) = pending {
+                free(link);
+            }
+        }
+    }
+}
+
+/// A pre-order iterator, visiting each node before its left and right subtrees
+pub struct PreorderIter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for PreorderIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if let Some(ref right) = node.right {
+            self.stack.push(right);
+        }
+        if let Some(ref left) = node.left {
+            self.stack.push(left);
+        }
+        Some(&node.item)
+    }
+}
+
+impl<'a, T> FusedIterator for PreorderIter<'a, T> {}
+
+/// A post-order iterator, visiting each node after its left and right subtrees
+pub struct PostorderIter<'a, T> {
+    // Nodes are paired with whether their children have already been pushed
+    stack: Vec<(&'a Node<T>, bool)>,
+}
+
+impl<'a, T> Iterator for PostorderIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, expanded)) = self.stack.pop() {
+            if expanded {
+                return Some(&node.item);
+            }
+            self.stack.push((node, true));
+            if let Some(ref right) = node.right {
+                self.stack.push((right, false));
+            }
+            if let Some(ref left) = node.left {
+                self.stack.push((left, false));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T> FusedIterator for PostorderIter<'a, T> {}
+
+/// A level-order iterator, visiting nodes breadth first from the root
+pub struct LevelOrderIter<'a, T> {
+    queue: VecDeque<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for LevelOrderIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        if let Some(ref left) = node.left {
+            self.queue.push_back(left);
+        }
+        if let Some(ref right) = node.right {
+            self.queue.push_back(right);
+        }
+        Some(&node.item)
+    }
+}
+
+impl<'a, T> FusedIterator for LevelOrderIter<'a, T> {}
+
+impl<T, C: Compare<T>> BinarySearchTree<T, C> {
+    /// Returns an iterator over the items in sorted order
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: InOrder::new(&self.root),
+        }
+    }
+
+    /// Returns a mutable iterator over the items in sorted order
+    ///
+    /// Items must not be modified in a way that changes their relative order,
+    /// as that would break the search tree invariant.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            inner: InOrder::new(&mut self.root),
+        }
+    }
+
+    /// Returns an iterator visiting each node before its subtrees
+    pub fn iter_preorder(&self) -> PreorderIter<'_, T> {
+        PreorderIter {
+            stack: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    /// Returns an iterator visiting each node after its subtrees
+    pub fn iter_postorder(&self) -> PostorderIter<'_, T> {
+        PostorderIter {
+            stack: self
+                .root
+                .as_deref()
+                .map(|node| (node, false))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Returns an iterator visiting the nodes level by level, left to right
+    pub fn iter_level_order(&self) -> LevelOrderIter<'_, T> {
+        LevelOrderIter {
+            queue: self.root.as_deref().into_iter().collect(),
+        }
+    }
+}
+
+impl<T, C: Compare<T>> IntoIterator for BinarySearchTree<T, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter::new(self.root.take())
+    }
+}
+
+impl<'a, T, C: Compare<T>> IntoIterator for &'a BinarySearchTree<T, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, C: Compare<T>> IntoIterator for &'a mut BinarySearchTree<T, C> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //       4
+    //     /   \
+    //    2     6
+    //   / \   / \
+    //  1   3 5   7
+    fn full_tree() -> BinarySearchTree<i32> {
+        let mut bst = BinarySearchTree::new();
+        for i in &[4, 2, 6, 1, 3, 5, 7] {
+            bst.insert(*i);
+        }
+        bst
+    }
+
+    #[test]
+    fn empty_tree_yields_nothing() {
+        let bst: BinarySearchTree<i32> = BinarySearchTree::new();
+        assert_eq!(bst.iter().next(), None);
+        assert_eq!(bst.iter().next_back(), None);
+        assert_eq!(bst.iter_preorder().next(), None);
+        assert_eq!(bst.iter_postorder().next(), None);
+        assert_eq!(bst.iter_level_order().next(), None);
+        assert_eq!(bst.into_iter().next(), None);
+    }
+
+    #[test]
+    fn iter_is_sorted() {
+        let mut bst = BinarySearchTree::new();
+        for i in &[5, 1, 9, 3, 7, 2, 8] {
+            bst.insert(*i);
+        }
+        assert_eq!(
+            bst.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 5, 7, 8, 9]
+        );
+    }
+
+    #[test]
+    fn iter_rev_is_reverse_sorted() {
+        let bst = full_tree();
+        assert_eq!(
+            bst.iter().rev().copied().collect::<Vec<_>>(),
+            vec![7, 6, 5, 4, 3, 2, 1]
+        );
+    }
+
+    #[test]
+    fn iter_from_both_ends_meets_in_the_middle() {
+        let bst = full_tree();
+        let mut iter = bst.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&7));
+        assert_eq!(iter.next_back(), Some(&6));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.next(), Some(&4));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn iter_mut_modifies_items() {
+        let mut bst = full_tree();
+        for item in bst.iter_mut() {
+            *item *= 10;
+        }
+        assert_eq!(
+            bst.iter().copied().collect::<Vec<_>>(),
+            vec![10, 20, 30, 40, 50, 60, 70]
+        );
+        assert!(bst.contains(&50));
+    }
+
+    #[test]
+    fn iter_mut_from_both_ends() {
+        let mut bst = full_tree();
+        let mut iter = bst.iter_mut();
+        assert_eq!(iter.next_back(), Some(&mut 7));
+        assert_eq!(iter.next(), Some(&mut 1));
+        assert_eq!(iter.count(), 5);
+    }
+
+    #[test]
+    fn into_iter_yields_owned_items_in_order() {
+        let mut bst = BinarySearchTree::new();
+        for s in &["b", "c", "a"] {
+            bst.insert(String::from(*s));
+        }
+        assert_eq!(bst.into_iter().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn into_iter_rev() {
+        let bst = full_tree();
+        assert_eq!(
+            bst.into_iter().rev().collect::<Vec<_>>(),
+            vec![7, 6, 5, 4, 3, 2, 1]
+        );
+    }
+
+    #[test]
+    fn for_loops_over_references() {
+        let mut bst = full_tree();
+        for item in &mut bst {
+            *item += 1;
+        }
+        let mut sum = 0;
+        for item in &bst {
+            sum += item;
+        }
+        assert_eq!(sum, 35);
+    }
+
+    #[test]
+    fn preorder() {
+        let bst = full_tree();
+        assert_eq!(
+            bst.iter_preorder().copied().collect::<Vec<_>>(),
+            vec![4, 2, 1, 3, 6, 5, 7]
+        );
+    }
+
+    #[test]
+    fn postorder() {
+        let bst = full_tree();
+        assert_eq!(
+            bst.iter_postorder().copied().collect::<Vec<_>>(),
+            vec![1, 3, 2, 5, 7, 6, 4]
+        );
+    }
+
+    #[test]
+    fn level_order() {
+        let bst = full_tree();
+        assert_eq!(
+            bst.iter_level_order().copied().collect::<Vec<_>>(),
+            vec![4, 2, 6, 1, 3, 5, 7]
+        );
+    }
+
+    #[test]
+    fn traversals_of_degenerate_tree() {
+        let mut bst = BinarySearchTree::new();
+        for i in 1..5 {
+            bst.insert(i);
+        }
+        assert_eq!(bst.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(
+            bst.iter_preorder().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            bst.iter_postorder().copied().collect::<Vec<_>>(),
+            vec![4, 3, 2, 1]
+        );
+        assert_eq!(
+            bst.iter_level_order().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+    }
+}
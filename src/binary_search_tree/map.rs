@@ -0,0 +1,813 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::RangeBounds;
+
+use super::augment::Augment;
+use super::range::{above_start, below_end};
+use super::Natural;
+
+struct Node<K: Ord, V, A: Augment<K, V>> {
+    key: K,
+    value: V,
+    // Aggregate of all entries in the subtree rooted at this node
+    summary: A::Summary,
+    left: BinarySearchTreeMap<K, V, A>,
+    right: BinarySearchTreeMap<K, V, A>,
+}
+
+impl<K: Ord, V, A: Augment<K, V>> Node<K, V, A> {
+    fn new(key: K, value: V) -> Self {
+        Node {
+            summary: A::summarize(&key, &value),
+            key,
+            value,
+            left: BinarySearchTreeMap::default(),
+            right: BinarySearchTreeMap::default(),
+        }
+    }
+
+    /// Recomputes the summary, assuming the summaries of both subtrees are up to date
+    fn update_summary(&mut self) {
+        let mut summary = A::summarize(&self.key, &self.value);
+        if let Some(ref left) = self.left.node {
+            summary = A::combine(&left.summary, &summary);
+        }
+        if let Some(ref right) = self.right.node {
+            summary = A::combine(&summary, &right.summary);
+        }
+        self.summary = summary;
+    }
+}
+
+// Nodes detached from the root downwards, each with the direction taken from it
+type Path<K, V, A> = Vec<(Box<Node<K, V, A>>, Ordering)>;
+
+/// An ordered map backed by an unbalanced binary search tree
+///
+/// Keys are ordered by their `Ord` implementation and every key is associated
+/// with exactly one value.
+///
+/// The map can optionally keep an aggregate of every subtree, described by an
+/// `Augment` implementation `A`, which allows aggregates over key ranges to be
+/// computed in O(height). Since such aggregates may depend on the values, the
+/// methods that hand out mutable references to values (`get_mut` and `entry`)
+/// are only available for plain maps.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::binary_search_tree::BinarySearchTreeMap;
+///
+/// let mut map = BinarySearchTreeMap::new();
+/// assert_eq!(map.insert("b", 2), None);
+/// assert_eq!(map.insert("a", 1), None);
+/// assert_eq!(map.insert("b", 3), Some(2));
+///
+/// assert_eq!(map.get("b"), Some(&3));
+/// assert_eq!(map.remove("a"), Some(1));
+/// assert_eq!(map.get("a"), None);
+/// ```
+pub struct BinarySearchTreeMap<K: Ord, V, A: Augment<K, V> = ()> {
+    node: Option<Box<Node<K, V, A>>>,
+}
+
+impl<K: Ord, V, A: Augment<K, V>> Default for BinarySearchTreeMap<K, V, A> {
+    fn default() -> Self {
+        Self { node: None }
+    }
+}
+
+impl<K: Ord, V, A: Augment<K, V>> Drop for BinarySearchTreeMap<K, V, A> {
+    fn drop(&mut self) {
+        // Frees the nodes one at a time, since dropping them recursively could overflow the stack
+        let mut nodes: Vec<_> = self.node.take().into_iter().collect();
+        while let Some(mut node) = nodes.pop() {
+            nodes.extend(node.left.node.take());
+            nodes.extend(node.right.node.take());
+        }
+    }
+}
+
+impl<K: Ord, V> BinarySearchTreeMap<K, V> {
+    /// Creates an empty map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a mutable reference to the value stored under `key`
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut map = self;
+        while let Some(ref mut node) = map.node {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Greater => map = &mut node.right,
+                Ordering::Less => map = &mut node.left,
+                Ordering::Equal => return Some(&mut node.value),
+            }
+        }
+        None
+    }
+
+    /// Gets the entry for `key`, for in-place insertion or modification
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::binary_search_tree::BinarySearchTreeMap;
+    ///
+    /// let mut counts = BinarySearchTreeMap::new();
+    /// for word in "a b a c a".split(' ') {
+    ///     *counts.entry(word).or_insert(0) += 1;
+    /// }
+    /// assert_eq!(counts.get("a"), Some(&3));
+    /// assert_eq!(counts.get("c"), Some(&1));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let mut map = self;
+        while map.node.is_some() {
+            match key.cmp(&map.node.as_ref().unwrap().key) {
+                Ordering::Greater => map = &mut map.node.as_mut().unwrap().right,
+                Ordering::Less => map = &mut map.node.as_mut().unwrap().left,
+                Ordering::Equal => return Entry::Occupied(OccupiedEntry { map }),
+            }
+        }
+        Entry::Vacant(VacantEntry { key, map })
+    }
+}
+
+impl<K: Ord, V, A: Augment<K, V>> BinarySearchTreeMap<K, V, A> {
+    /// Inserts a key-value pair, returning the value previously stored under `key`
+    ///
+    /// If the key was already present its value is replaced, but the stored key is kept.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if !Self::keeps_summaries() {
+            return self.descend(|node| key.cmp(&node.key)).set_root(key, value);
+        }
+        let (path, mut subtree) = self.detach_path(|node| key.cmp(&node.key));
+        let previous = subtree.set_root(key, value);
+        self.attach_path(path, subtree);
+        previous
+    }
+
+    /// Returns a reference to the value stored under `key`
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut map = self;
+        while let Some(ref node) = map.node {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Greater => map = &node.right,
+                Ordering::Less => map = &node.left,
+                Ordering::Equal => return Some(&node.value),
+            }
+        }
+        None
+    }
+
+    /// Returns `true` if the map contains a value for `key`
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Returns `false` if summaries are zero-sized, as for plain maps, so that they never need updating
+    fn keeps_summaries() -> bool {
+        mem::size_of::<A::Summary>() != 0
+    }
+
+    /// Returns the subtree at the end of the path from the root that `direction` leads along
+    ///
+    /// The path ends at an empty subtree, or at the node for which `direction`
+    /// returns `Ordering::Equal`. Changing the subtree leaves the summaries on
+    /// the path outdated, so this is only used when they are never updated.
+    fn descend<F: FnMut(&Node<K, V, A>) -> Ordering>(&mut self, mut direction: F) -> &mut Self {
+        let mut map = self;
+        loop {
+            let ordering = match map.node {
+                Some(ref node) => direction(node),
+                None => return map,
+            };
+            map = match ordering {
+                Ordering::Greater => &mut map.node.as_mut().unwrap().right,
+                Ordering::Less => &mut map.node.as_mut().unwrap().left,
+                Ordering::Equal => return map,
+            };
+        }
+    }
+
+    /// Detaches the nodes on the path from the root that `direction` leads along
+    ///
+    /// Like `descend`, but returns the detached nodes, each paired with the
+    /// direction taken from it and missing the child in that direction, along
+    /// with the subtree the path ends at. This keeps the summaries on the path
+    /// reachable for `attach_path` without recursion, which could overflow the
+    /// stack on the long paths of an unbalanced tree.
+    fn detach_path<F: FnMut(&Node<K, V, A>) -> Ordering>(
+        &mut self,
+        mut direction: F,
+    ) -> (Path<K, V, A>, Self) {
+        let mut path = Vec::new();
+        let mut subtree = mem::take(self);
+        while let Some(mut node) = subtree.node.take() {
+            let ordering = direction(&node);
+            subtree = match ordering {
+                Ordering::Greater => mem::take(&mut node.right),
+                Ordering::Less => mem::take(&mut node.left),
+                Ordering::Equal => {
+                    subtree.node = Some(node);
+                    break;
+                }
+            };
+            path.push((node, ordering));
+        }
+        (path, subtree)
+    }
+
+    /// Reattaches the nodes of a path detached by `detach_path` above `subtree`, updating their summaries bottom-up
+    fn attach_path(&mut self, mut path: Path<K, V, A>, mut subtree: Self) {
+        while let Some((mut node, ordering)) = path.pop() {
+            if ordering == Ordering::Greater {
+                node.right = subtree;
+            } else {
+                node.left = subtree;
+            }
+            node.update_summary();
+            subtree = Self { node: Some(node) };
+        }
+        *self = subtree;
+    }
+
+    /// Stores a pair at the root of a subtree that is either empty or holds an equal key
+    fn set_root(&mut self, key: K, value: V) -> Option<V> {
+        match self.node {
+            Some(ref mut node) => {
+                let previous = mem::replace(&mut node.value, value);
+                node.update_summary();
+                Some(previous)
+            }
+            None => {
+                self.node = Some(Box::new(Node::new(key, value)));
+                None
+            }
+        }
+    }
+
+    fn find_and_delete_min(&mut self) -> (K, V) {
+        let to_min = |node: &Node<K, V, A>| {
+            if node.left.node.is_some() {
+                Ordering::Less
+            } else {
+                Ordering::Equal
+            }
+        };
+        if !Self::keeps_summaries() {
+            return self.descend(to_min).unlink_root();
+        }
+        let (path, mut subtree) = self.detach_path(to_min);
+        let min = subtree.unlink_root();
+        self.attach_path(path, subtree);
+        min
+    }
+
+    /// Removes the root of a subtree, which must not have a left child
+    fn unlink_root(&mut self) -> (K, V) {
+        let mut node = self.node.take().unwrap();
+        self.node = node.right.node.take();
+        (node.key, node.value)
+    }
+
+    fn delete_node(&mut self) -> V {
+        let node = self.node.as_mut().unwrap();
+        if node.left.node.is_some() && node.right.node.is_some() {
+            let (key, value) = node.right.find_and_delete_min();
+            node.key = key;
+            let value = mem::replace(&mut node.value, value);
+            node.update_summary();
+            value
+        } else {
+            let mut node = self.node.take().unwrap();
+            if node.left.node.is_some() {
+                self.node = node.left.node.take();
+            } else if node.right.node.is_some() {
+                self.node = node.right.node.take();
+            }
+            node.value
+        }
+    }
+
+    /// Removes `key` from the map, returning the value that was stored under it
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if !Self::keeps_summaries() {
+            let map = self.descend(|node| key.cmp(node.key.borrow()));
+            map.node.as_ref()?;
+            return Some(map.delete_node());
+        }
+        let (path, mut subtree) = self.detach_path(|node| key.cmp(node.key.borrow()));
+        let removed = if subtree.node.is_some() {
+            Some(subtree.delete_node())
+        } else {
+            None
+        };
+        self.attach_path(path, subtree);
+        removed
+    }
+
+    /// Combines the summaries of all entries whose keys lie inside `range`
+    ///
+    /// Only the paths to the two bounds of the range are visited. Subtrees
+    /// hanging off them contribute their stored summary as a whole, so this
+    /// takes O(height) calls to `Augment::combine`.
+    pub fn aggregate<Q, R>(&self, range: R) -> A::Summary
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let mut map = self;
+        while let Some(ref node) = map.node {
+            if !above_start(&Natural, node.key.borrow(), start) {
+                map = &node.right;
+            } else if !below_end(&Natural, node.key.borrow(), end) {
+                map = &node.left;
+            } else {
+                // The paths to the two bounds split here
+                let mut lower_summary = A::identity();
+                let mut lower = &node.left;
+                while let Some(ref node) = lower.node {
+                    if above_start(&Natural, node.key.borrow(), start) {
+                        let entry = A::summarize(&node.key, &node.value);
+                        lower_summary =
+                            A::combine(&A::combine(&entry, &node.right.summary()), &lower_summary);
+                        lower = &node.left;
+                    } else {
+                        lower = &node.right;
+                    }
+                }
+
+                let mut upper_summary = A::identity();
+                let mut upper = &node.right;
+                while let Some(ref node) = upper.node {
+                    if below_end(&Natural, node.key.borrow(), end) {
+                        let entry = A::summarize(&node.key, &node.value);
+                        upper_summary =
+                            A::combine(&upper_summary, &A::combine(&node.left.summary(), &entry));
+                        upper = &node.right;
+                    } else {
+                        upper = &node.left;
+                    }
+                }
+
+                let entry = A::summarize(&node.key, &node.value);
+                return A::combine(&A::combine(&lower_summary, &entry), &upper_summary);
+            }
+        }
+        A::identity()
+    }
+
+    fn summary(&self) -> A::Summary {
+        self.node
+            .as_ref()
+            .map_or_else(A::identity, |node| node.summary.clone())
+    }
+
+    /// Splits a non-empty map into its left subtree, root entry and right subtree
+    pub(crate) fn split_root(&self) -> Option<(&Self, (&K, &V), &Self)> {
+        self.node
+            .as_ref()
+            .map(|node| (&node.left, (&node.key, &node.value), &node.right))
+    }
+}
+
+/// A view into a single entry of a `BinarySearchTreeMap`
+pub enum Entry<'a, K: Ord, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+/// An entry whose key is present in the map
+pub struct OccupiedEntry<'a, K: Ord, V> {
+    map: &'a mut BinarySearchTreeMap<K, V>,
+}
+
+/// An entry whose key is absent from the map
+///
+/// Holds the empty subtree the key will be inserted into.
+pub struct VacantEntry<'a, K: Ord, V> {
+    key: K,
+    map: &'a mut BinarySearchTreeMap<K, V>,
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    /// Returns the key of this entry
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts `default` if the entry is vacant, and returns a reference to the value
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Inserts the result of `default` if the entry is vacant, and returns a reference to the value
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Applies `f` to the value if the entry is occupied
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(ref mut entry) = self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: Ord, V: Default> Entry<'a, K, V> {
+    /// Inserts `V::default()` if the entry is vacant, and returns a reference to the value
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    /// Returns the key of this entry
+    pub fn key(&self) -> &K {
+        &self.map.node.as_ref().unwrap().key
+    }
+
+    /// Returns a reference to the value of this entry
+    pub fn get(&self) -> &V {
+        &self.map.node.as_ref().unwrap().value
+    }
+
+    /// Returns a mutable reference to the value of this entry
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.node.as_mut().unwrap().value
+    }
+
+    /// Converts the entry into a mutable reference bound to the map's lifetime
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.node.as_mut().unwrap().value
+    }
+
+    /// Replaces the value of this entry, returning the old value
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry from the map, returning its value
+    pub fn remove(self) -> V {
+        self.map.delete_node()
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    /// Returns the key that would be used when inserting through this entry
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes ownership of the key
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts a value under the entry's key, and returns a reference to it
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.node = Some(Box::new(Node::new(self.key, value)));
+        &mut self.map.node.as_mut().unwrap().value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_search_tree::{Count, MaxValue, Sum};
+    use std::thread;
+
+    #[test]
+    fn empty_map_doesnt_contain_key() {
+        let map: BinarySearchTreeMap<i32, i32> = BinarySearchTreeMap::new();
+        assert_eq!(map.get(&1), None);
+        assert!(!map.contains_key(&1));
+    }
+
+    #[test]
+    fn inserted_value_can_be_retrieved() {
+        let mut map = BinarySearchTreeMap::new();
+        assert_eq!(map.insert(1, 'a'), None);
+        assert_eq!(map.insert(3, 'c'), None);
+        assert_eq!(map.insert(2, 'b'), None);
+
+        assert_eq!(map.get(&1), Some(&'a'));
+        assert_eq!(map.get(&2), Some(&'b'));
+        assert_eq!(map.get(&3), Some(&'c'));
+        assert_eq!(map.get(&4), None);
+    }
+
+    #[test]
+    fn insert_existing_key_returns_previous_value() {
+        let mut map = BinarySearchTreeMap::new();
+        map.insert(1, 'a');
+
+        assert_eq!(map.insert(1, 'b'), Some('a'));
+        assert_eq!(map.get(&1), Some(&'b'));
+    }
+
+    #[test]
+    fn get_mut_modifies_value() {
+        let mut map = BinarySearchTreeMap::new();
+        map.insert(2, 10);
+        map.insert(1, 20);
+
+        *map.get_mut(&1).unwrap() += 1;
+
+        assert_eq!(map.get(&1), Some(&21));
+        assert_eq!(map.get_mut(&3), None);
+    }
+
+    #[test]
+    fn get_by_borrowed_key() {
+        let mut map = BinarySearchTreeMap::new();
+        map.insert(String::from("foo"), 1);
+
+        assert_eq!(map.get("foo"), Some(&1));
+        assert_eq!(map.remove("foo"), Some(1));
+    }
+
+    #[test]
+    fn remove_returns_removed_value() {
+        let mut map = BinarySearchTreeMap::new();
+        map.insert(1, 'a');
+
+        assert_eq!(map.remove(&1), Some('a'));
+        assert_eq!(map.remove(&1), None);
+        assert!(!map.contains_key(&1));
+    }
+
+    #[test]
+    fn other_keys_exist_after_root_with_two_children_removed() {
+        let mut map = BinarySearchTreeMap::new();
+        map.insert(2, 'b');
+        map.insert(1, 'a');
+        map.insert(5, 'e');
+        map.insert(3, 'c');
+        // The roots succeeding node gets a right child
+        map.insert(4, 'd');
+
+        assert_eq!(map.remove(&2), Some('b'));
+
+        assert_eq!(map.get(&1), Some(&'a'));
+        assert_eq!(map.get(&3), Some(&'c'));
+        assert_eq!(map.get(&4), Some(&'d'));
+        assert_eq!(map.get(&5), Some(&'e'));
+        assert_eq!(map.get(&2), None);
+    }
+
+    #[test]
+    fn remove_node_with_one_child() {
+        let mut map = BinarySearchTreeMap::new();
+        map.insert(3, 'c');
+        map.insert(1, 'a');
+        map.insert(2, 'b');
+
+        assert_eq!(map.remove(&1), Some('a'));
+        assert_eq!(map.remove(&3), Some('c'));
+        assert_eq!(map.get(&2), Some(&'b'));
+    }
+
+    #[test]
+    fn entry_or_insert_counts_occurrences() {
+        let mut map = BinarySearchTreeMap::new();
+        for c in "abracadabra".chars() {
+            *map.entry(c).or_insert(0) += 1;
+        }
+
+        assert_eq!(map.get(&'a'), Some(&5));
+        assert_eq!(map.get(&'b'), Some(&2));
+        assert_eq!(map.get(&'r'), Some(&2));
+        assert_eq!(map.get(&'c'), Some(&1));
+        assert_eq!(map.get(&'d'), Some(&1));
+    }
+
+    #[test]
+    fn entry_and_modify_only_touches_occupied_entries() {
+        let mut map = BinarySearchTreeMap::new();
+        map.insert(1, 1);
+
+        map.entry(1).and_modify(|v| *v += 10).or_default();
+        map.entry(2).and_modify(|v| *v += 10).or_default();
+
+        assert_eq!(map.get(&1), Some(&11));
+        assert_eq!(map.get(&2), Some(&0));
+    }
+
+    #[test]
+    fn occupied_entry_insert_and_remove() {
+        let mut map = BinarySearchTreeMap::new();
+        map.insert(2, 'b');
+        map.insert(1, 'a');
+        map.insert(3, 'c');
+
+        match map.entry(2) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), &2);
+                assert_eq!(entry.insert('x'), 'b');
+                assert_eq!(entry.remove(), 'x');
+            }
+            Entry::Vacant(_) => panic!("Expected an occupied entry"),
+        }
+
+        assert!(!map.contains_key(&2));
+        assert_eq!(map.get(&1), Some(&'a'));
+        assert_eq!(map.get(&3), Some(&'c'));
+    }
+
+    #[test]
+    fn vacant_entry_insert() {
+        let mut map = BinarySearchTreeMap::new();
+        map.insert(2, 'b');
+
+        match map.entry(1) {
+            Entry::Occupied(_) => panic!("Expected a vacant entry"),
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.key(), &1);
+                *entry.insert('a') = 'z';
+            }
+        }
+
+        assert_eq!(map.get(&1), Some(&'z'));
+    }
+
+    fn sum_map(entries: &[(i32, i32)]) -> BinarySearchTreeMap<i32, i32, Sum> {
+        let mut map = BinarySearchTreeMap::default();
+        for (key, value) in entries {
+            map.insert(*key, *value);
+        }
+        map
+    }
+
+    #[test]
+    fn degenerate_map_does_not_overflow_the_stack() {
+        // Recursing along a path as long as the map would overflow this stack
+        let n = 3000;
+        thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(move || {
+                let mut sorted = sum_map(&[]);
+                for key in 0..n {
+                    sorted.insert(key, 1);
+                }
+                assert_eq!(sorted.insert(n - 2, 10), Some(1));
+                assert_eq!(sorted.remove(&(n - 1)), Some(1));
+                assert_eq!(sorted.aggregate(..), n + 8);
+                assert_eq!(sorted.aggregate(n - 3..), 11);
+
+                // Removing the root moves up the end of a long left spine
+                let mut spine = sum_map(&[(0, 0)]);
+                for key in (1..n).rev() {
+                    spine.insert(key, key);
+                }
+                assert_eq!(spine.remove(&0), Some(0));
+                assert_eq!(spine.aggregate(..), n * (n - 1) / 2);
+                assert_eq!(spine.aggregate(..3), 3);
+
+                // Plain maps take the same paths without detaching them
+                let mut plain = BinarySearchTreeMap::new();
+                for key in 0..n {
+                    plain.insert(key, key);
+                }
+                assert_eq!(plain.insert(n - 1, 0), Some(n - 1));
+                assert_eq!(plain.remove(&(n - 2)), Some(n - 2));
+                assert_eq!(plain.remove(&0), Some(0));
+                assert_eq!(plain.get(&(n - 1)), Some(&0));
+                assert!(!plain.contains_key(&0));
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn aggregate_of_empty_map_is_identity() {
+        let map = sum_map(&[]);
+        assert_eq!(map.aggregate(..), 0);
+    }
+
+    #[test]
+    fn aggregate_sums_values_inside_range() {
+        let entries = [
+            (5, 50),
+            (2, 20),
+            (8, 80),
+            (1, 10),
+            (3, 30),
+            (7, 70),
+            (9, 90),
+        ];
+        let map = sum_map(&entries);
+
+        for start in 0..11 {
+            for end in start..11 {
+                let expected: i32 = entries
+                    .iter()
+                    .filter(|(key, _)| (start..end).contains(key))
+                    .map(|(_, value)| value)
+                    .sum();
+                assert_eq!(map.aggregate(start..end), expected);
+            }
+        }
+        assert_eq!(map.aggregate(..), 350);
+        assert_eq!(map.aggregate(..=2), 30);
+        assert_eq!(map.aggregate(8..), 170);
+    }
+
+    #[test]
+    fn summaries_are_updated_by_insert_and_remove() {
+        let mut map = sum_map(&[(5, 50), (2, 20), (8, 80), (1, 10), (3, 30), (7, 70)]);
+
+        assert_eq!(map.insert(3, 300), Some(30));
+        assert_eq!(map.aggregate(..), 530);
+
+        // Two children, so the successor is moved into the root
+        assert_eq!(map.remove(&5), Some(50));
+        assert_eq!(map.aggregate(..), 480);
+        assert_eq!(map.aggregate(4..), 150);
+
+        assert_eq!(map.remove(&1), Some(10));
+        assert_eq!(map.remove(&8), Some(80));
+        assert_eq!(map.aggregate(..), 390);
+        assert_eq!(map.aggregate(..3), 20);
+    }
+
+    #[test]
+    fn max_value_and_count_augmentations() {
+        let mut max: BinarySearchTreeMap<char, i32, MaxValue> = BinarySearchTreeMap::default();
+        let mut count: BinarySearchTreeMap<char, i32, Count> = BinarySearchTreeMap::default();
+        for (key, value) in "hello world".chars().zip(0..) {
+            max.insert(key, value);
+            count.insert(key, value);
+        }
+
+        assert_eq!(max.aggregate(..), Some(10));
+        assert_eq!(max.aggregate('e'..'m'), Some(9));
+        assert_eq!(max.aggregate('x'..), None);
+        assert_eq!(count.aggregate(..), 8);
+        assert_eq!(count.aggregate('e'..='l'), 3);
+    }
+
+    /// Concatenates keys, to check that summaries are combined in key order
+    struct Concat;
+
+    impl Augment<char, ()> for Concat {
+        type Summary = String;
+
+        fn identity() -> String {
+            String::new()
+        }
+
+        fn summarize(key: &char, _value: &()) -> String {
+            key.to_string()
+        }
+
+        fn combine(left: &String, right: &String) -> String {
+            format!("{}{}", left, right)
+        }
+    }
+
+    #[test]
+    fn aggregate_combines_in_key_order() {
+        let mut map: BinarySearchTreeMap<char, (), Concat> = BinarySearchTreeMap::default();
+        for key in "qwertyuiop".chars() {
+            map.insert(key, ());
+        }
+
+        assert_eq!(map.aggregate(..), "eiopqrtuwy");
+        assert_eq!(map.aggregate('f'..'u'), "iopqrt");
+        map.remove(&'q');
+        assert_eq!(map.aggregate('f'..'u'), "ioprt");
+    }
+}
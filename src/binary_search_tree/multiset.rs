@@ -0,0 +1,263 @@
+use std::borrow::Borrow;
+use std::iter::FusedIterator;
+
+use super::map::BinarySearchTreeMap;
+
+type CountMap<T> = BinarySearchTreeMap<T, usize>;
+
+/// A sorted multiset backed by an unbalanced binary search tree
+///
+/// Unlike `BinarySearchTree`, inserting an item equal to one already present
+/// keeps both. Equal items are indistinguishable, so each distinct item is
+/// stored once together with the number of times it was inserted. Iteration
+/// yields every copy, in sorted order.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::binary_search_tree::BinarySearchMultiset;
+///
+/// let mut words = BinarySearchMultiset::new();
+/// for word in "the cat saw the dog".split(' ') {
+///     words.insert(word);
+/// }
+/// assert_eq!(words.count("the"), 2);
+/// assert_eq!(words.len(), 5);
+///
+/// assert!(words.remove_one("the"));
+/// assert_eq!(words.count("the"), 1);
+/// assert_eq!(words.remove_all("cat"), 1);
+/// assert_eq!(words.iter().copied().collect::<Vec<_>>(), vec!["dog", "saw", "the"]);
+/// ```
+pub struct BinarySearchMultiset<T: Ord> {
+    counts: CountMap<T>,
+    len: usize,
+}
+
+impl<T: Ord> Default for BinarySearchMultiset<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> BinarySearchMultiset<T> {
+    pub fn new() -> Self {
+        Self {
+            counts: CountMap::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of items, counting every copy
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts a copy of `item`, returning the number of copies now present
+    pub fn insert(&mut self, item: T) -> usize {
+        self.len += 1;
+        let count = self.counts.entry(item).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// Returns the number of copies of `item`
+    pub fn count<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.counts.contains_key(item)
+    }
+
+    /// Removes a single copy of `item`, returning whether there was one
+    pub fn remove_one<Q>(&mut self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.counts.get_mut(item) {
+            None => return false,
+            Some(count) if *count > 1 => *count -= 1,
+            Some(_) => {
+                self.counts.remove(item);
+            }
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// Removes every copy of `item`, returning the number of removed copies
+    pub fn remove_all<Q>(&mut self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = self.counts.remove(item).unwrap_or(0);
+        self.len -= removed;
+        removed
+    }
+
+    /// Returns an iterator over the items in sorted order, repeating every copy
+    pub fn iter(&self) -> MultisetIter<'_, T> {
+        MultisetIter {
+            counts: self.counts(),
+            current: None,
+        }
+    }
+
+    /// Returns an iterator over the distinct items and their counts, in sorted order
+    pub fn counts(&self) -> MultisetCounts<'_, T> {
+        let mut counts = MultisetCounts { stack: Vec::new() };
+        counts.push_left_spine(&self.counts);
+        counts
+    }
+}
+
+/// An iterator over the distinct items of a `BinarySearchMultiset` and their counts
+pub struct MultisetCounts<'a, T: Ord> {
+    // Entries whose left subtrees have been visited, along with their right subtrees
+    stack: Vec<(&'a T, usize, &'a CountMap<T>)>,
+}
+
+impl<'a, T: Ord> MultisetCounts<'a, T> {
+    fn push_left_spine(&mut self, mut map: &'a CountMap<T>) {
+        while let Some((left, (item, count), right)) = map.split_root() {
+            self.stack.push((item, *count, right));
+            map = left;
+        }
+    }
+}
+
+impl<'a, T: Ord> Iterator for MultisetCounts<'a, T> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (item, count, right) = self.stack.pop()?;
+        self.push_left_spine(right);
+        Some((item, count))
+    }
+}
+
+impl<'a, T: Ord> FusedIterator for MultisetCounts<'a, T> {}
+
+/// An iterator over every copy of the items of a `BinarySearchMultiset`
+pub struct MultisetIter<'a, T: Ord> {
+    counts: MultisetCounts<'a, T>,
+    // The item being repeated and the number of copies left to yield
+    current: Option<(&'a T, usize)>,
+}
+
+impl<'a, T: Ord> Iterator for MultisetIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((item, remaining)) = self.current {
+                if remaining > 0 {
+                    self.current = Some((item, remaining - 1));
+                    return Some(item);
+                }
+            }
+            self.current = Some(self.counts.next()?);
+        }
+    }
+}
+
+impl<'a, T: Ord> FusedIterator for MultisetIter<'a, T> {}
+
+impl<'a, T: Ord> IntoIterator for &'a BinarySearchMultiset<T> {
+    type Item = &'a T;
+    type IntoIter = MultisetIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multiset(items: &[i32]) -> BinarySearchMultiset<i32> {
+        let mut multiset = BinarySearchMultiset::new();
+        for item in items {
+            multiset.insert(*item);
+        }
+        multiset
+    }
+
+    #[test]
+    fn empty_multiset() {
+        let multiset = multiset(&[]);
+        assert!(multiset.is_empty());
+        assert_eq!(multiset.count(&1), 0);
+        assert_eq!(multiset.iter().count(), 0);
+    }
+
+    #[test]
+    fn insert_keeps_duplicates() {
+        let mut multiset = multiset(&[3, 1, 3, 2]);
+        assert_eq!(multiset.insert(3), 3);
+        assert_eq!(multiset.insert(4), 1);
+
+        assert_eq!(multiset.len(), 6);
+        assert_eq!(multiset.count(&3), 3);
+        assert!(multiset.contains(&1));
+        assert_eq!(
+            multiset.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 3, 3, 4]
+        );
+        assert_eq!(
+            multiset.counts().collect::<Vec<_>>(),
+            vec![(&1, 1), (&2, 1), (&3, 3), (&4, 1)]
+        );
+    }
+
+    #[test]
+    fn remove_one_removes_a_single_copy() {
+        let mut multiset = multiset(&[2, 1, 2]);
+        assert!(multiset.remove_one(&2));
+        assert_eq!(multiset.count(&2), 1);
+        assert!(multiset.remove_one(&2));
+        assert!(!multiset.contains(&2));
+        assert!(!multiset.remove_one(&2));
+
+        assert_eq!(multiset.len(), 1);
+        assert_eq!(multiset.iter().copied().collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn remove_all_removes_every_copy() {
+        let mut multiset = multiset(&[5, 5, 1, 5, 9]);
+        assert_eq!(multiset.remove_all(&5), 3);
+        assert_eq!(multiset.remove_all(&5), 0);
+
+        assert_eq!(multiset.len(), 2);
+        assert_eq!(multiset.iter().copied().collect::<Vec<_>>(), vec![1, 9]);
+    }
+
+    #[test]
+    fn lookups_by_borrowed_form() {
+        let mut multiset = BinarySearchMultiset::new();
+        multiset.insert(String::from("bid"));
+        multiset.insert(String::from("bid"));
+
+        assert_eq!(multiset.count("bid"), 2);
+        assert!(multiset.remove_one("bid"));
+        assert_eq!(multiset.remove_all("bid"), 1);
+        assert!(multiset.is_empty());
+    }
+}
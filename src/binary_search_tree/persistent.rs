@@ -0,0 +1,410 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+type Link<T> = Option<Arc<Node<T>>>;
+
+struct Node<T> {
+    item: T,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T> Drop for Node<T> {
+    fn drop(&mut self) {
+        // Children owned by this node alone are unwrapped onto a stack, so a
+        // long path is freed one node at a time instead of recursively
+        let mut nodes: Vec<_> = self.left.take().into_iter().collect();
+        nodes.extend(self.right.take());
+        while let Some(node) = nodes.pop() {
+            if let Ok(mut node) = Arc::try_unwrap(node) {
+                nodes.extend(node.left.take());
+                nodes.extend(node.right.take());
+            }
+        }
+    }
+}
+
+/// Rebuilds the path leading to a replaced subtree, sharing everything off the path
+///
+/// `path` lists the nodes from the root down to the parent of the replaced
+/// subtree, along with the direction taken from each of them.
+fn copy_path<T: Clone>(path: &[(&Arc<Node<T>>, Ordering)], mut subtree: Link<T>) -> Link<T> {
+    for (node, direction) in path.iter().rev() {
+        let (left, right) = match direction {
+            Ordering::Less => (subtree, node.right.clone()),
+            _ => (node.left.clone(), subtree),
+        };
+        subtree = Some(Arc::new(Node {
+            item: node.item.clone(),
+            left,
+            right,
+        }));
+    }
+    subtree
+}
+
+/// An immutable binary search tree where every modification creates a new version
+///
+/// `insert` and `remove` leave the tree they are called on untouched and
+/// return a new tree. Only the nodes on the path to the modified position are
+/// copied, every other subtree is shared between the versions through
+/// reference counting. Keeping an old version around therefore costs
+/// O(height) memory per modification, and cloning a tree is O(1).
+///
+/// Like `BinarySearchTree` the tree is not rebalanced. Nodes are shared
+/// through `Arc`, so versions can be sent to other threads when `T` allows it.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::binary_search_tree::PersistentBinarySearchTree;
+///
+/// let empty = PersistentBinarySearchTree::new();
+/// let v1 = empty.insert(2).insert(1);
+/// let v2 = v1.insert(3);
+/// let v3 = v2.remove(&1);
+///
+/// assert_eq!(v1.iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+/// assert_eq!(v2.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+/// assert_eq!(v3.iter().copied().collect::<Vec<_>>(), vec![2, 3]);
+/// assert!(empty.is_empty());
+/// ```
+pub struct PersistentBinarySearchTree<T: Ord> {
+    root: Link<T>,
+    len: usize,
+}
+
+impl<T: Ord> Clone for PersistentBinarySearchTree<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Ord> Default for PersistentBinarySearchTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> PersistentBinarySearchTree<T> {
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of items in this version
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the stored item equal to `item`
+    pub fn get<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &self.root;
+        while let Some(ref node) = link {
+            match item.cmp(node.item.borrow()) {
+                Ordering::Greater => link = &node.right,
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.item),
+            }
+        }
+        None
+    }
+
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(item).is_some()
+    }
+
+    /// Returns the smallest item
+    pub fn min(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(ref left) = node.left {
+            node = left;
+        }
+        Some(&node.item)
+    }
+
+    /// Returns the largest item
+    pub fn max(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(ref right) = node.right {
+            node = right;
+        }
+        Some(&node.item)
+    }
+
+    /// Returns an iterator over the items in sorted order
+    pub fn iter(&self) -> PersistentIter<'_, T> {
+        let mut iter = PersistentIter { stack: Vec::new() };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+}
+
+impl<T: Ord + Clone> PersistentBinarySearchTree<T> {
+    /// Returns a new version with `item` inserted
+    ///
+    /// The items on the path to the new node are cloned. If the item is
+    /// already present, the returned version shares all nodes with this one.
+    pub fn insert(&self, item: T) -> Self {
+        let mut path = Vec::new();
+        let mut link = &self.root;
+        while let Some(ref node) = link {
+            let direction = item.cmp(&node.item);
+            match direction {
+                Ordering::Greater => link = &node.right,
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return self.clone(),
+            }
+            path.push((node, direction));
+        }
+        let leaf = Some(Arc::new(Node {
+            item,
+            left: None,
+            right: None,
+        }));
+        Self {
+            root: copy_path(&path, leaf),
+            len: self.len + 1,
+        }
+    }
+
+    /// Returns a new version with the item equal to `item` removed
+    ///
+    /// If the item is not present, the returned version shares all nodes with this one.
+    pub fn remove<Q>(&self, item: &Q) -> Self
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut path = Vec::new();
+        let mut link = &self.root;
+        let removed = loop {
+            let node = match link {
+                Some(ref node) => node,
+                None => return self.clone(),
+            };
+            let direction = item.cmp(node.item.borrow());
+            match direction {
+                Ordering::Greater => link = &node.right,
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => break node,
+            }
+            path.push((node, direction));
+        };
+
+        let replacement = match (&removed.left, &removed.right) {
+            (None, child) | (child, None) => child.clone(),
+            (left, Some(right)) => {
+                // Replace the item with its successor, copying the path to it
+                let mut successor_path = Vec::new();
+                let mut successor = right;
+                while let Some(ref left) = successor.left {
+                    successor_path.push((successor, Ordering::Less));
+                    successor = left;
+                }
+                Some(Arc::new(Node {
+                    item: successor.item.clone(),
+                    left: left.clone(),
+                    right: copy_path(&successor_path, successor.right.clone()),
+                }))
+            }
+        };
+        Self {
+            root: copy_path(&path, replacement),
+            len: self.len - 1,
+        }
+    }
+}
+
+/// An in-order iterator over the items of a `PersistentBinarySearchTree`
+pub struct PersistentIter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T> PersistentIter<'a, T> {
+    fn push_left_spine(&mut self, mut link: &'a Link<T>) {
+        while let Some(ref node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for PersistentIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        Some(&node.item)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a PersistentBinarySearchTree<T> {
+    type Item = &'a T;
+    type IntoIter = PersistentIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn tree(items: &[i32]) -> PersistentBinarySearchTree<i32> {
+        items
+            .iter()
+            .fold(PersistentBinarySearchTree::new(), |tree, item| {
+                tree.insert(*item)
+            })
+    }
+
+    fn collect(tree: &PersistentBinarySearchTree<i32>) -> Vec<i32> {
+        tree.iter().copied().collect()
+    }
+
+    fn child(link: &Link<i32>, direction: Ordering) -> &Link<i32> {
+        let node = link.as_ref().unwrap();
+        match direction {
+            Ordering::Less => &node.left,
+            _ => &node.right,
+        }
+    }
+
+    fn same_node(a: &Link<i32>, b: &Link<i32>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn empty_tree() {
+        let tree = tree(&[]);
+        assert!(tree.is_empty());
+        assert!(!tree.contains(&1));
+        assert_eq!(tree.min(), None);
+        assert_eq!(collect(&tree.remove(&1)), vec![]);
+    }
+
+    #[test]
+    fn old_versions_are_unchanged() {
+        let v1 = tree(&[5, 2, 8]);
+        let v2 = v1.insert(1).insert(9);
+        let v3 = v2.remove(&5);
+        let v4 = v3.remove(&2);
+
+        assert_eq!(collect(&v1), vec![2, 5, 8]);
+        assert_eq!(collect(&v2), vec![1, 2, 5, 8, 9]);
+        assert_eq!(collect(&v3), vec![1, 2, 8, 9]);
+        assert_eq!(collect(&v4), vec![1, 8, 9]);
+        assert_eq!((v1.len(), v2.len(), v3.len(), v4.len()), (3, 5, 4, 3));
+    }
+
+    #[test]
+    fn insert_shares_subtrees_off_the_path() {
+        //     5
+        //    / \
+        //   2   8
+        //  / \
+        // 1   3
+        let old = tree(&[5, 2, 8, 1, 3]);
+        let new = old.insert(4);
+
+        assert!(!same_node(&old.root, &new.root));
+        assert!(same_node(
+            child(&old.root, Ordering::Greater),
+            child(&new.root, Ordering::Greater)
+        ));
+        let old_two = child(&old.root, Ordering::Less);
+        let new_two = child(&new.root, Ordering::Less);
+        assert!(same_node(
+            child(old_two, Ordering::Less),
+            child(new_two, Ordering::Less)
+        ));
+        assert!(new.contains(&4));
+        assert!(!old.contains(&4));
+    }
+
+    #[test]
+    fn remove_node_with_two_children_shares_left_subtree() {
+        let old = tree(&[5, 2, 8, 1, 3, 7, 9, 6]);
+        let new = old.remove(&5);
+
+        assert_eq!(new.get(&6), Some(&6));
+        assert_eq!(new.root.as_ref().unwrap().item, 6);
+        assert!(same_node(
+            child(&old.root, Ordering::Less),
+            child(&new.root, Ordering::Less)
+        ));
+        assert_eq!(collect(&new), vec![1, 2, 3, 6, 7, 8, 9]);
+        assert_eq!(collect(&old), vec![1, 2, 3, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn unchanged_versions_share_root() {
+        let tree = tree(&[2, 1, 3]);
+        assert!(same_node(&tree.root, &tree.insert(2).root));
+        assert!(same_node(&tree.root, &tree.remove(&4).root));
+        assert!(same_node(&tree.root, &tree.clone().root));
+    }
+
+    #[test]
+    fn removing_every_item_in_turn() {
+        let items = [50, 20, 80, 10, 30, 25, 35, 70, 60, 75, 90];
+        let full = tree(&items);
+        let mut sorted = items.to_vec();
+        sorted.sort_unstable();
+        for item in &items {
+            let removed = full.remove(item);
+            let expected: Vec<_> = sorted.iter().copied().filter(|i| i != item).collect();
+            assert_eq!(collect(&removed), expected);
+            assert_eq!(removed.len(), items.len() - 1);
+        }
+        assert_eq!(collect(&full), sorted);
+    }
+
+    #[test]
+    fn lookups_by_borrowed_form() {
+        let tree = PersistentBinarySearchTree::new()
+            .insert(String::from("b"))
+            .insert(String::from("a"));
+        assert!(tree.contains("a"));
+        assert_eq!(tree.remove("a").len(), 1);
+    }
+
+    #[test]
+    fn deep_versions_drop_without_overflowing_the_stack() {
+        // Recursing along a path as long as the tree would overflow this stack
+        thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(|| {
+                let sorted = tree(&(0..5000).collect::<Vec<_>>());
+                let shared = sorted.insert(5000);
+                drop(sorted);
+                assert_eq!(shared.len(), 5001);
+                assert_eq!(shared.max(), Some(&5000));
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+}
@@ -0,0 +1,345 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::iter::FusedIterator;
+use std::ops::{Bound, RangeBounds};
+
+use super::iter::{split_ref, InOrder, Pending};
+use super::{count_prefix, delete_node, size, BinarySearchTree, Compare, Link};
+
+pub(crate) fn above_start<C: Compare<Q>, Q: ?Sized>(
+    comparator: &C,
+    item: &Q,
+    start: Bound<&Q>,
+) -> bool {
+    match start {
+        Bound::Included(start) => comparator.compare(item, start) != Ordering::Less,
+        Bound::Excluded(start) => comparator.compare(item, start) == Ordering::Greater,
+        Bound::Unbounded => true,
+    }
+}
+
+pub(crate) fn below_end<C: Compare<Q>, Q: ?Sized>(
+    comparator: &C,
+    item: &Q,
+    end: Bound<&Q>,
+) -> bool {
+    match end {
+        Bound::Included(end) => comparator.compare(item, end) != Ordering::Greater,
+        Bound::Excluded(end) => comparator.compare(item, end) == Ordering::Less,
+        Bound::Unbounded => true,
+    }
+}
+
+/// An in-order iterator over the items of a `BinarySearchTree` that fall inside a range
+pub struct Range<'a, T> {
+    inner: InOrder<&'a T, &'a Link<T>>,
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next(split_ref)
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Range<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back(split_ref)
+    }
+}
+
+impl<'a, T> FusedIterator for Range<'a, T> {}
+
+impl<T, C: Compare<T>> BinarySearchTree<T, C> {
+    /// Returns an iterator over the items inside `range`, in sorted order
+    ///
+    /// Only the two paths leading to the bounds of the range are inspected up
+    /// front. Subtrees hanging off those paths lie entirely inside or entirely
+    /// outside the range, so they are either iterated lazily or skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut bst = BinarySearchTree::new();
+    /// for i in &[5, 2, 8, 1, 9, 3] {
+    ///     bst.insert(*i);
+    /// }
+    /// assert_eq!(bst.range(2..8).copied().collect::<Vec<_>>(), vec![2, 3, 5]);
+    /// assert_eq!(bst.range(4..).copied().collect::<Vec<_>>(), vec![5, 8, 9]);
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T>
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let comparator = &self.comparator;
+        let mut pending = VecDeque::new();
+        let mut link = &self.root;
+        while let Some(ref node) = link {
+            if !above_start(comparator, node.item.borrow(), start) {
+                link = &node.right;
+            } else if !below_end(comparator, node.item.borrow(), end) {
+                link = &node.left;
+            } else {
+                // The paths to the two bounds split here
+                pending.push_back(Pending::Item(&node.item));
+
+                let mut lower = &node.left;
+                while let Some(ref node) = lower {
+                    if above_start(comparator, node.item.borrow(), start) {
+                        pending.push_front(Pending::Tree(&node.right));
+                        pending.push_front(Pending::Item(&node.item));
+                        lower = &node.left;
+                    } else {
+                        lower = &node.right;
+                    }
+                }
+
+                let mut upper = &node.right;
+                while let Some(ref node) = upper {
+                    if below_end(comparator, node.item.borrow(), end) {
+                        pending.push_back(Pending::Tree(&node.left));
+                        pending.push_back(Pending::Item(&node.item));
+                        upper = &node.right;
+                    } else {
+                        upper = &node.left;
+                    }
+                }
+                break;
+            }
+        }
+        Range {
+            inner: InOrder::from_pending(pending),
+        }
+    }
+
+    /// Counts the items inside `range`
+    pub fn count_range<Q, R>(&self, range: R) -> usize
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let comparator = &self.comparator;
+        let up_to_end = count_prefix(&self.root, |item| below_end(comparator, item.borrow(), end));
+        let before_start = count_prefix(&self.root, |item| {
+            !above_start(comparator, item.borrow(), start)
+        });
+        up_to_end.saturating_sub(before_start)
+    }
+
+    /// Removes every item inside `range`, returning the number of removed items
+    ///
+    /// Like `range`, this only walks the paths to the two bounds. Subtrees
+    /// between them are unlinked as a whole, and the remaining parts are joined
+    /// together by deleting the node where the two paths split.
+    pub fn remove_range<Q, R>(&mut self, range: R) -> usize
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let removed = self.count_range((start, end));
+        if removed == 0 {
+            return 0;
+        }
+        let comparator = &self.comparator;
+
+        let mut link = &mut self.root;
+        loop {
+            let item = &link.as_ref().unwrap().item;
+            if !above_start(comparator, item.borrow(), start) {
+                let node = link.as_mut().unwrap();
+                node.size -= removed;
+                link = &mut node.right;
+            } else if !below_end(comparator, item.borrow(), end) {
+                let node = link.as_mut().unwrap();
+                node.size -= removed;
+                link = &mut node.left;
+            } else {
+                break;
+            }
+        }
+        let node = link.as_mut().unwrap();
+
+        let mut lower_removed = size(&node.left)
+            - count_prefix(&node.left, |item| {
+                !above_start(comparator, item.borrow(), start)
+            });
+        let mut lower = &mut node.left;
+        while lower_removed > 0 {
+            if above_start(comparator, lower.as_ref().unwrap().item.borrow(), start) {
+                // The node and everything to its right is inside the range
+                let mut removed_node = lower.take().unwrap();
+                lower_removed -= 1 + size(&removed_node.right);
+                *lower = removed_node.left.take();
+            } else {
+                let kept_node = lower.as_mut().unwrap();
+                kept_node.size -= lower_removed;
+                lower = &mut kept_node.right;
+            }
+        }
+
+        let mut upper_removed = count_prefix(&node.right, |item| {
+            below_end(comparator, item.borrow(), end)
+        });
+        let mut upper = &mut node.right;
+        while upper_removed > 0 {
+            if below_end(comparator, upper.as_ref().unwrap().item.borrow(), end) {
+                // The node and everything to its left is inside the range
+                let mut removed_node = upper.take().unwrap();
+                upper_removed -= 1 + size(&removed_node.left);
+                *upper = removed_node.right.take();
+            } else {
+                let kept_node = upper.as_mut().unwrap();
+                kept_node.size -= upper_removed;
+                upper = &mut kept_node.left;
+            }
+        }
+
+        node.size = 1 + size(&node.left) + size(&node.right);
+        delete_node(link);
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(items: &[i32]) -> BinarySearchTree<i32> {
+        let mut bst = BinarySearchTree::new();
+        for item in items {
+            bst.insert(*item);
+        }
+        bst
+    }
+
+    fn collect<'a>(iter: impl Iterator<Item = &'a i32>) -> Vec<i32> {
+        iter.copied().collect()
+    }
+
+    #[test]
+    fn range_of_empty_tree() {
+        let bst = tree(&[]);
+        assert_eq!(collect(bst.range(..)), vec![]);
+    }
+
+    #[test]
+    fn unbounded_range_contains_everything() {
+        let bst = tree(&[5, 2, 8, 1, 9, 3, 7]);
+        assert_eq!(collect(bst.range(..)), vec![1, 2, 3, 5, 7, 8, 9]);
+    }
+
+    #[test]
+    fn range_bounds() {
+        let bst = tree(&[5, 2, 8, 1, 9, 3, 7]);
+        assert_eq!(collect(bst.range(2..8)), vec![2, 3, 5, 7]);
+        assert_eq!(collect(bst.range(2..=8)), vec![2, 3, 5, 7, 8]);
+        assert_eq!(collect(bst.range(4..)), vec![5, 7, 8, 9]);
+        assert_eq!(collect(bst.range(..4)), vec![1, 2, 3]);
+        assert_eq!(collect(bst.range(..=3)), vec![1, 2, 3]);
+        assert_eq!(
+            collect(bst.range((Bound::Excluded(2), Bound::Excluded(8)))),
+            vec![3, 5, 7]
+        );
+    }
+
+    #[test]
+    fn range_without_items() {
+        let bst = tree(&[5, 2, 8, 1, 9]);
+        assert_eq!(collect(bst.range(3..5)), vec![]);
+        assert_eq!(collect(bst.range(10..)), vec![]);
+        assert_eq!(collect(bst.range(5..5)), vec![]);
+    }
+
+    #[test]
+    fn range_is_double_ended() {
+        let bst = tree(&[5, 2, 8, 1, 9, 3, 7]);
+        let mut range = bst.range(2..9);
+        assert_eq!(range.next_back(), Some(&8));
+        assert_eq!(range.next(), Some(&2));
+        assert_eq!(collect(range.rev()), vec![7, 5, 3]);
+    }
+
+    #[test]
+    fn range_of_degenerate_tree() {
+        let bst = tree(&(0..100).collect::<Vec<_>>());
+        assert_eq!(collect(bst.range(40..45)), vec![40, 41, 42, 43, 44]);
+    }
+
+    #[test]
+    fn count_range() {
+        let bst = tree(&[5, 2, 8, 1, 9, 3, 7]);
+        assert_eq!(bst.count_range(..), 7);
+        assert_eq!(bst.count_range(3..=8), 4);
+        assert_eq!(bst.count_range(10..), 0);
+    }
+
+    #[test]
+    fn remove_range_removes_only_items_inside() {
+        let mut bst = tree(&[5, 2, 8, 1, 9, 3, 7, 4, 6]);
+        assert_eq!(bst.remove_range(3..7), 4);
+
+        assert_eq!(collect(bst.iter()), vec![1, 2, 7, 8, 9]);
+        for i in 3..7 {
+            assert!(!bst.contains(&i));
+        }
+    }
+
+    #[test]
+    fn remove_range_with_split_below_root() {
+        let mut bst = tree(&[10, 5, 15, 2, 7, 6, 8, 12]);
+        assert_eq!(bst.remove_range(6..=7), 2);
+        assert_eq!(collect(bst.iter()), vec![2, 5, 8, 10, 12, 15]);
+    }
+
+    #[test]
+    fn remove_range_without_items() {
+        let mut bst = tree(&[5, 2, 8]);
+        assert_eq!(bst.remove_range(3..5), 0);
+        assert_eq!(collect(bst.iter()), vec![2, 5, 8]);
+    }
+
+    #[test]
+    fn remove_unbounded_range_empties_tree() {
+        let mut bst = tree(&[5, 2, 8, 1, 9, 3, 7]);
+        assert_eq!(bst.remove_range(..), 7);
+        assert_eq!(collect(bst.iter()), vec![]);
+    }
+
+    #[test]
+    fn tree_is_usable_after_remove_range() {
+        let mut bst = tree(&[5, 2, 8, 1, 9, 3, 7]);
+        bst.remove_range(..=5);
+        bst.insert(4);
+        bst.delete(8);
+
+        assert_eq!(collect(bst.iter()), vec![4, 7, 9]);
+        assert_eq!(bst.len(), 3);
+    }
+
+    #[test]
+    fn remove_range_keeps_sizes_consistent() {
+        let mut bst = tree(&[50, 20, 80, 10, 30, 25, 35, 70, 60, 75, 90]);
+        assert_eq!(bst.remove_range(22..72), 6);
+
+        assert_eq!(bst.len(), 5);
+        let sorted = [10, 20, 75, 80, 90];
+        for (k, item) in sorted.iter().enumerate() {
+            assert_eq!(bst.select(k), Some(item));
+            assert_eq!(bst.rank(item), k);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::fmt::{self, Debug, Display, Write};
+
+use super::{BinarySearchTree, Compare, Node};
+
+/// Formats the tree as a set of its items in sorted order
+impl<T: Debug, C: Compare<T>> Debug for BinarySearchTree<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Draws the structure of the tree as ASCII art, like `render_ascii`
+impl<T: Display, C: Compare<T>> Display for BinarySearchTree<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render_ascii())
+    }
+}
+
+impl<T: Display, C: Compare<T>> BinarySearchTree<T, C> {
+    /// Draws the structure of the tree as ASCII art, one node per line
+    ///
+    /// Children are drawn below their parent and marked with `L` or `R`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut bst = BinarySearchTree::new();
+    /// for i in &[5, 2, 8, 1, 3, 9] {
+    ///     bst.insert(*i);
+    /// }
+    /// assert_eq!(
+    ///     bst.render_ascii(),
+    ///     concat!(
+    ///         "5\n",
+    ///         "|-- L: 2\n",
+    ///         "|   |-- L: 1\n",
+    ///         "|   `-- R: 3\n",
+    ///         "`-- R: 8\n",
+    ///         "    `-- R: 9\n",
+    ///     )
+    /// );
+    /// ```
+    pub fn render_ascii(&self) -> String {
+        let mut output = String::new();
+        let root = match self.root {
+            Some(ref node) => node,
+            None => return output,
+        };
+        // Each entry holds a node, the line to draw it on and the prefix for the lines below it
+        let mut stack = vec![(root.as_ref(), format!("{}", root.item), String::new())];
+        while let Some((node, line, prefix)) = stack.pop() {
+            output.push_str(&line);
+            output.push('\n');
+
+            let children: Vec<(&str, &Node<T>)> = vec![("L", &node.left), ("R", &node.right)]
+                .into_iter()
+                .filter_map(|(side, child)| child.as_deref().map(|node| (side, node)))
+                .collect();
+            // Pushed in reverse so that the left child is drawn first
+            for (i, (side, child)) in children.iter().enumerate().rev() {
+                let last = i + 1 == children.len();
+                let (branch, indent) = if last {
+                    ("`-- ", "    ")
+                } else {
+                    ("|-- ", "|   ")
+                };
+                stack.push((
+                    child,
+                    format!("{}{}{}: {}", prefix, branch, side, child.item),
+                    format!("{}{}", prefix, indent),
+                ));
+            }
+        }
+        output
+    }
+
+    /// Exports the structure of the tree in the Graphviz DOT format
+    ///
+    /// Edges are labeled `L` or `R`, so the output can be rendered with
+    /// `dot -Tsvg` to see how operations reshape the tree.
+    pub fn to_dot(&self) -> String {
+        let mut output = String::from("digraph {\n");
+        let mut next_id = 0;
+        let mut stack = Vec::new();
+        if let Some(ref node) = self.root {
+            stack.push((node.as_ref(), None));
+        }
+        while let Some((node, parent)) = stack.pop() {
+            let id = next_id;
+            next_id += 1;
+            let label = node
+                .item
+                .to_string()
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"");
+            writeln!(output, "    node{} [label=\"{}\"];", id, label).unwrap();
+            if let Some((parent, side)) = parent {
+                writeln!(
+                    output,
+                    "    node{} -> node{} [label=\"{}\"];",
+                    parent, id, side
+                )
+                .unwrap();
+            }
+            if let Some(ref right) = node.right {
+                stack.push((right.as_ref(), Some((id, "R"))));
+            }
+            if let Some(ref left) = node.left {
+                stack.push((left.as_ref(), Some((id, "L"))));
+            }
+        }
+        output.push_str("}\n");
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(items: &[i32]) -> BinarySearchTree<i32> {
+        let mut bst = BinarySearchTree::new();
+        for item in items {
+            bst.insert(*item);
+        }
+        bst
+    }
+
+    #[test]
+    fn debug_lists_items_as_set() {
+        assert_eq!(format!("{:?}", tree(&[])), "{}");
+        assert_eq!(format!("{:?}", tree(&[3, 1, 2])), "{1, 2, 3}");
+    }
+
+    #[test]
+    fn display_draws_tree() {
+        let bst = tree(&[2, 1, 3]);
+        assert_eq!(bst.to_string(), "2\n|-- L: 1\n`-- R: 3\n");
+        assert_eq!(format!("{}", bst), bst.render_ascii());
+        assert_eq!(tree(&[]).to_string(), "");
+    }
+
+    #[test]
+    fn render_empty_tree() {
+        assert_eq!(tree(&[]).render_ascii(), "");
+    }
+
+    #[test]
+    fn render_shows_sides_of_single_children() {
+        let bst = tree(&[4, 2, 6, 3, 5]);
+        assert_eq!(
+            bst.render_ascii(),
+            concat!(
+                "4\n",
+                "|-- L: 2\n",
+                "|   `-- R: 3\n",
+                "`-- R: 6\n",
+                "    `-- L: 5\n",
+            )
+        );
+    }
+
+    #[test]
+    fn render_degenerate_tree() {
+        let bst = tree(&(0..1000).collect::<Vec<_>>());
+        let rendered = bst.render_ascii();
+        assert_eq!(rendered.lines().count(), 1000);
+        assert!(rendered.ends_with("`-- R: 999\n"));
+    }
+
+    #[test]
+    fn dot_export() {
+        let bst = tree(&[2, 1, 3]);
+        assert_eq!(
+            bst.to_dot(),
+            concat!(
+                "digraph {\n",
+                "    node0 [label=\"2\"];\n",
+                "    node1 [label=\"1\"];\n",
+                "    node0 -> node1 [label=\"L\"];\n",
+                "    node2 [label=\"3\"];\n",
+                "    node0 -> node2 [label=\"R\"];\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn dot_export_escapes_labels() {
+        let mut bst = BinarySearchTree::new();
+        bst.insert(String::from("say \"hi\""));
+        assert!(bst.to_dot().contains(r#"[label="say \"hi\""]"#));
+    }
+}
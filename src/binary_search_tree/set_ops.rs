@@ -0,0 +1,333 @@
+use std::cmp::Ordering;
+use std::iter::{FusedIterator, Peekable};
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+
+use super::iter::Iter;
+use super::{BinarySearchTree, Compare, Natural};
+
+/// A lazy iterator over the items in either of two `BinarySearchTree`s
+pub struct Union<'a, T, C: Compare<T> = Natural> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+impl<'a, T, C: Compare<T>> Iterator for Union<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match (self.a.peek().copied(), self.b.peek().copied()) {
+            (Some(a), Some(b)) => match self.comparator.compare(a, b) {
+                Ordering::Less => self.a.next(),
+                Ordering::Greater => self.b.next(),
+                Ordering::Equal => {
+                    self.b.next();
+                    self.a.next()
+                }
+            },
+            (Some(_), None) => self.a.next(),
+            (None, _) => self.b.next(),
+        }
+    }
+}
+
+impl<'a, T, C: Compare<T>> FusedIterator for Union<'a, T, C> {}
+
+/// A lazy iterator over the items in both of two `BinarySearchTree`s
+pub struct Intersection<'a, T, C: Compare<T> = Natural> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+impl<'a, T, C: Compare<T>> Iterator for Intersection<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (a, b) = (self.a.peek().copied()?, self.b.peek().copied()?);
+            match self.comparator.compare(a, b) {
+                Ordering::Less => {
+                    self.a.next();
+                }
+                Ordering::Greater => {
+                    self.b.next();
+                }
+                Ordering::Equal => {
+                    self.b.next();
+                    return self.a.next();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T, C: Compare<T>> FusedIterator for Intersection<'a, T, C> {}
+
+/// A lazy iterator over the items in one `BinarySearchTree` but not in another
+pub struct Difference<'a, T, C: Compare<T> = Natural> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+impl<'a, T, C: Compare<T>> Iterator for Difference<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let a = self.a.peek().copied()?;
+            let b = match self.b.peek().copied() {
+                Some(b) => b,
+                None => return self.a.next(),
+            };
+            match self.comparator.compare(a, b) {
+                Ordering::Less => return self.a.next(),
+                Ordering::Greater => {
+                    self.b.next();
+                }
+                Ordering::Equal => {
+                    self.a.next();
+                    self.b.next();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T, C: Compare<T>> FusedIterator for Difference<'a, T, C> {}
+
+/// A lazy iterator over the items in exactly one of two `BinarySearchTree`s
+pub struct SymmetricDifference<'a, T, C: Compare<T> = Natural> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+    comparator: &'a C,
+}
+
+impl<'a, T, C: Compare<T>> Iterator for SymmetricDifference<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.a.peek().copied(), self.b.peek().copied()) {
+                (Some(a), Some(b)) => match self.comparator.compare(a, b) {
+                    Ordering::Less => return self.a.next(),
+                    Ordering::Greater => return self.b.next(),
+                    Ordering::Equal => {
+                        self.a.next();
+                        self.b.next();
+                    }
+                },
+                (Some(_), None) => return self.a.next(),
+                (None, _) => return self.b.next(),
+            }
+        }
+    }
+}
+
+impl<'a, T, C: Compare<T>> FusedIterator for SymmetricDifference<'a, T, C> {}
+
+/// Set operations that merge the sorted sequences of two trees
+///
+/// The iterators visit each item of both trees at most once, so consuming one
+/// takes O(n + m) time. The operators build a new tree of minimum height from
+/// the merged sequence, also in linear time.
+impl<T, C: Compare<T>> BinarySearchTree<T, C> {
+    /// Returns the items in `self` or `other`, in sorted order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut a = BinarySearchTree::new();
+    /// let mut b = BinarySearchTree::new();
+    /// for i in &[1, 3, 5] {
+    ///     a.insert(*i);
+    /// }
+    /// for i in &[3, 4] {
+    ///     b.insert(*i);
+    /// }
+    /// assert_eq!(a.union(&b).copied().collect::<Vec<_>>(), vec![1, 3, 4, 5]);
+    /// assert_eq!((&a & &b).iter().copied().collect::<Vec<_>>(), vec![3]);
+    /// ```
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, T, C> {
+        Union {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+            comparator: &self.comparator,
+        }
+    }
+
+    /// Returns the items in both `self` and `other`, in sorted order
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, T, C> {
+        Intersection {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+            comparator: &self.comparator,
+        }
+    }
+
+    /// Returns the items in `self` but not in `other`, in sorted order
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, T, C> {
+        Difference {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+            comparator: &self.comparator,
+        }
+    }
+
+    /// Returns the items in exactly one of `self` and `other`, in sorted order
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> SymmetricDifference<'a, T, C> {
+        SymmetricDifference {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+            comparator: &self.comparator,
+        }
+    }
+}
+
+impl<T: Clone, C: Compare<T>> BitOr<&BinarySearchTree<T, C>> for &BinarySearchTree<T, C> {
+    type Output = BinarySearchTree<T, C>;
+
+    /// Returns a new tree with the items in `self` or `rhs`
+    fn bitor(self, rhs: &BinarySearchTree<T, C>) -> BinarySearchTree<T, C> {
+        BinarySearchTree::from_sorted_vec(
+            self.union(rhs).cloned().collect(),
+            self.comparator.clone(),
+        )
+    }
+}
+
+impl<T: Clone, C: Compare<T>> BitAnd<&BinarySearchTree<T, C>> for &BinarySearchTree<T, C> {
+    type Output = BinarySearchTree<T, C>;
+
+    /// Returns a new tree with the items in both `self` and `rhs`
+    fn bitand(self, rhs: &BinarySearchTree<T, C>) -> BinarySearchTree<T, C> {
+        BinarySearchTree::from_sorted_vec(
+            self.intersection(rhs).cloned().collect(),
+            self.comparator.clone(),
+        )
+    }
+}
+
+impl<T: Clone, C: Compare<T>> Sub<&BinarySearchTree<T, C>> for &BinarySearchTree<T, C> {
+    type Output = BinarySearchTree<T, C>;
+
+    /// Returns a new tree with the items in `self` but not in `rhs`
+    fn sub(self, rhs: &BinarySearchTree<T, C>) -> BinarySearchTree<T, C> {
+        BinarySearchTree::from_sorted_vec(
+            self.difference(rhs).cloned().collect(),
+            self.comparator.clone(),
+        )
+    }
+}
+
+impl<T: Clone, C: Compare<T>> BitXor<&BinarySearchTree<T, C>> for &BinarySearchTree<T, C> {
+    type Output = BinarySearchTree<T, C>;
+
+    /// Returns a new tree with the items in exactly one of `self` and `rhs`
+    fn bitxor(self, rhs: &BinarySearchTree<T, C>) -> BinarySearchTree<T, C> {
+        BinarySearchTree::from_sorted_vec(
+            self.symmetric_difference(rhs).cloned().collect(),
+            self.comparator.clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn tree(items: &[i32]) -> BinarySearchTree<i32> {
+        let mut bst = BinarySearchTree::new();
+        for item in items {
+            bst.insert(*item);
+        }
+        bst
+    }
+
+    fn collect<'a>(iter: impl Iterator<Item = &'a i32>) -> Vec<i32> {
+        iter.copied().collect()
+    }
+
+    #[test]
+    fn operations_with_empty_tree() {
+        let a = tree(&[2, 1, 3]);
+        let empty = tree(&[]);
+
+        assert_eq!(collect(a.union(&empty)), vec![1, 2, 3]);
+        assert_eq!(collect(empty.union(&a)), vec![1, 2, 3]);
+        assert_eq!(collect(a.intersection(&empty)), vec![]);
+        assert_eq!(collect(a.difference(&empty)), vec![1, 2, 3]);
+        assert_eq!(collect(empty.difference(&a)), vec![]);
+        assert_eq!(collect(empty.symmetric_difference(&a)), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn iterators_merge_overlapping_trees() {
+        let a = tree(&[5, 1, 9, 3, 7]);
+        let b = tree(&[4, 3, 9, 10]);
+
+        assert_eq!(collect(a.union(&b)), vec![1, 3, 4, 5, 7, 9, 10]);
+        assert_eq!(collect(a.intersection(&b)), vec![3, 9]);
+        assert_eq!(collect(a.difference(&b)), vec![1, 5, 7]);
+        assert_eq!(collect(b.difference(&a)), vec![4, 10]);
+        assert_eq!(collect(a.symmetric_difference(&b)), vec![1, 4, 5, 7, 10]);
+    }
+
+    #[test]
+    fn operators_build_new_trees() {
+        let a = tree(&[5, 1, 9, 3, 7]);
+        let b = tree(&[4, 3, 9, 10]);
+
+        let union = &a | &b;
+        assert_eq!(collect(union.iter()), vec![1, 3, 4, 5, 7, 9, 10]);
+        assert_eq!(union.len(), 7);
+        assert_eq!(union.select(3), Some(&5));
+        assert_eq!(collect((&a & &b).iter()), vec![3, 9]);
+        assert_eq!(collect((&a - &b).iter()), vec![1, 5, 7]);
+        assert_eq!(collect((&a ^ &b).iter()), vec![1, 4, 5, 7, 10]);
+    }
+
+    #[test]
+    fn built_trees_have_minimum_height() {
+        // Both inputs are degenerate, but the result is not
+        let a = tree(&(0..500).collect::<Vec<_>>());
+        let b = tree(&(250..1023).collect::<Vec<_>>());
+        let union = &a | &b;
+
+        assert_eq!(union.len(), 1023);
+        let mut levels = 0;
+        let mut level: Vec<_> = union.root.iter().collect();
+        while !level.is_empty() {
+            levels += 1;
+            level = level
+                .iter()
+                .flat_map(|node| node.left.iter().chain(node.right.iter()))
+                .collect();
+        }
+        assert_eq!(levels, 10);
+    }
+
+    #[test]
+    fn operations_match_btree_set() {
+        let a_items: Vec<_> = (0..300).map(|i| (i * 37) % 211).collect();
+        let b_items: Vec<_> = (0..200).map(|i| (i * 53) % 197).collect();
+        let (a, b) = (tree(&a_items), tree(&b_items));
+        let a_set: BTreeSet<_> = a_items.into_iter().collect();
+        let b_set: BTreeSet<_> = b_items.into_iter().collect();
+
+        assert_eq!(collect(a.union(&b)), collect(a_set.union(&b_set)));
+        assert_eq!(
+            collect(a.intersection(&b)),
+            collect(a_set.intersection(&b_set))
+        );
+        assert_eq!(collect(a.difference(&b)), collect(a_set.difference(&b_set)));
+        assert_eq!(
+            collect((&a ^ &b).iter()),
+            collect(a_set.symmetric_difference(&b_set))
+        );
+    }
+}
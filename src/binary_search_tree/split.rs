@@ -0,0 +1,270 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::mem;
+
+use super::iter::IntoIter;
+use super::{
+    find_and_delete_min, from_sorted_exact, leftmost, rightmost, size, BinarySearchTree, Compare,
+    Link, Node,
+};
+
+impl<T, C: Compare<T>> BinarySearchTree<T, C> {
+    /// Moves every item greater than or equal to `item` into a new tree
+    ///
+    /// Only the path to `item` is walked: subtrees hanging off it are moved as
+    /// a whole, so this takes O(height) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut low = BinarySearchTree::new();
+    /// for i in &[5, 2, 8, 1, 9, 3] {
+    ///     low.insert(*i);
+    /// }
+    /// let high = low.split_off(&5);
+    ///
+    /// assert_eq!(low.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// assert_eq!(high.iter().copied().collect::<Vec<_>>(), vec![5, 8, 9]);
+    /// ```
+    pub fn split_off<Q>(&mut self, item: &Q) -> Self
+    where
+        T: Borrow<Q>,
+        C: Compare<Q>,
+        Q: ?Sized,
+    {
+        // Number of items to move out of the subtree at `kept`
+        let mut moved = self.len() - self.rank(item);
+        let mut split = self.empty();
+        let mut kept = &mut self.root;
+        let mut hole = &mut split.root;
+        while moved > 0 {
+            if self
+                .comparator
+                .compare(kept.as_ref().unwrap().item.borrow(), item)
+                == Ordering::Less
+            {
+                let node = kept.as_mut().unwrap();
+                node.size -= moved;
+                kept = &mut node.right;
+            } else {
+                // The node and its right subtree move, and its left subtree takes its place
+                let mut node = kept.take().unwrap();
+                *kept = node.left.take();
+                let moved_left = moved - 1 - size(&node.right);
+                node.size = moved;
+                *hole = Some(node);
+                hole = &mut hole.as_mut().unwrap().left;
+                moved = moved_left;
+            }
+        }
+        split
+    }
+
+    /// Moves every item of `other` into this tree, leaving `other` empty
+    ///
+    /// If all items of one tree are less than all items of the other, the
+    /// trees are concatenated in O(height) time by making the smallest item of
+    /// the upper tree the new root. Otherwise the items are merged, which takes
+    /// O(n + m) time and produces a tree of minimum height. Both trees are
+    /// assumed to use the same ordering.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut low = BinarySearchTree::new();
+    /// let mut high = BinarySearchTree::new();
+    /// for i in 0..5 {
+    ///     low.insert(i);
+    ///     high.insert(i + 10);
+    /// }
+    /// low.append(&mut high);
+    ///
+    /// assert_eq!(low.len(), 10);
+    /// assert!(high.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        let mut other = other.root.take();
+        if other.is_none() {
+            return;
+        }
+        if self.root.is_none() {
+            self.root = other;
+            return;
+        }
+
+        if self
+            .comparator
+            .compare(rightmost(&self.root).unwrap(), leftmost(&other).unwrap())
+            == Ordering::Less
+        {
+            self.concat(other);
+        } else if self
+            .comparator
+            .compare(rightmost(&other).unwrap(), leftmost(&self.root).unwrap())
+            == Ordering::Less
+        {
+            mem::swap(&mut self.root, &mut other);
+            self.concat(other);
+        } else {
+            let merged: Vec<T> = Merge {
+                a: IntoIter::new(self.root.take()).peekable(),
+                b: IntoIter::new(other).peekable(),
+                comparator: &self.comparator,
+            }
+            .collect();
+            let len = merged.len();
+            self.root = from_sorted_exact(&mut merged.into_iter(), len);
+        }
+    }
+
+    /// Concatenates a non-empty subtree whose items are all greater than the items of this tree
+    fn concat(&mut self, mut upper: Link<T>) {
+        let root = find_and_delete_min(&mut upper);
+        let lower = self.root.take();
+        self.root = Some(Box::new(Node {
+            item: root,
+            size: 1 + size(&lower) + size(&upper),
+            left: lower,
+            right: upper,
+        }));
+    }
+}
+
+/// Merges the items of two sorted iterators, keeping only one of two equal items
+pub(super) struct Merge<'a, I: Iterator, C> {
+    pub(super) a: Peekable<I>,
+    pub(super) b: Peekable<I>,
+    pub(super) comparator: &'a C,
+}
+
+impl<'a, T, I: Iterator<Item = T>, C: Compare<T>> Iterator for Merge<'a, I, C> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let ordering = match (self.a.peek(), self.b.peek()) {
+            (Some(a), Some(b)) => self.comparator.compare(a, b),
+            (Some(_), None) => Ordering::Less,
+            (None, _) => Ordering::Greater,
+        };
+        match ordering {
+            Ordering::Less => self.a.next(),
+            Ordering::Greater => self.b.next(),
+            Ordering::Equal => {
+                self.b.next();
+                self.a.next()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(items: &[i32]) -> BinarySearchTree<i32> {
+        let mut bst = BinarySearchTree::new();
+        for item in items {
+            bst.insert(*item);
+        }
+        bst
+    }
+
+    fn collect(bst: &BinarySearchTree<i32>) -> Vec<i32> {
+        bst.iter().copied().collect()
+    }
+
+    /// Checks that the ranks and selections agree with the sorted items
+    fn assert_sizes(bst: &BinarySearchTree<i32>) {
+        let items = collect(bst);
+        assert_eq!(bst.len(), items.len());
+        for (k, item) in items.iter().enumerate() {
+            assert_eq!(bst.select(k), Some(item));
+            assert_eq!(bst.rank(item), k);
+        }
+    }
+
+    #[test]
+    fn split_off_at_every_position() {
+        let items = [50, 20, 80, 10, 30, 25, 35, 70, 60, 75, 90];
+        for key in 0..100 {
+            let mut low = tree(&items);
+            let high = low.split_off(&key);
+
+            assert!(collect(&low).iter().all(|item| *item < key));
+            assert!(collect(&high).iter().all(|item| *item >= key));
+            assert_eq!(low.len() + high.len(), items.len());
+            assert_sizes(&low);
+            assert_sizes(&high);
+        }
+    }
+
+    #[test]
+    fn split_off_empty_tree() {
+        let mut bst = tree(&[]);
+        assert!(bst.split_off(&1).is_empty());
+    }
+
+    #[test]
+    fn split_off_degenerate_tree() {
+        let mut low = tree(&(0..1000).collect::<Vec<_>>());
+        let high = low.split_off(&600);
+        assert_eq!(low.len(), 600);
+        assert_eq!(high.len(), 400);
+        assert_eq!(high.min(), Some(&600));
+    }
+
+    #[test]
+    fn append_disjoint_trees_in_either_order() {
+        let mut low = tree(&[5, 2, 8]);
+        let mut high = tree(&[15, 12, 18]);
+        low.append(&mut high);
+        assert_eq!(collect(&low), vec![2, 5, 8, 12, 15, 18]);
+        assert!(high.is_empty());
+        assert_sizes(&low);
+
+        let mut high = tree(&[15, 12, 18]);
+        let mut low = tree(&[5, 2, 8]);
+        high.append(&mut low);
+        assert_eq!(collect(&high), vec![2, 5, 8, 12, 15, 18]);
+        assert_sizes(&high);
+    }
+
+    #[test]
+    fn append_with_empty_trees() {
+        let mut bst = tree(&[1, 2]);
+        bst.append(&mut tree(&[]));
+        assert_eq!(collect(&bst), vec![1, 2]);
+
+        let mut empty = tree(&[]);
+        empty.append(&mut bst);
+        assert_eq!(collect(&empty), vec![1, 2]);
+        assert!(bst.is_empty());
+    }
+
+    #[test]
+    fn append_overlapping_trees_merges_them() {
+        let mut a = tree(&[1, 5, 9, 3]);
+        let mut b = tree(&[4, 5, 10]);
+        a.append(&mut b);
+        assert_eq!(collect(&a), vec![1, 3, 4, 5, 9, 10]);
+        assert_sizes(&a);
+    }
+
+    #[test]
+    fn split_off_and_append_round_trip() {
+        let items = [50, 20, 80, 10, 30, 25, 35, 70, 60, 75, 90];
+        let mut low = tree(&items);
+        let mut high = low.split_off(&40);
+        low.append(&mut high);
+
+        let mut sorted = items.to_vec();
+        sorted.sort_unstable();
+        assert_eq!(collect(&low), sorted);
+        assert_sizes(&low);
+    }
+}
@@ -0,0 +1,292 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+use super::{BinarySearchTree, Compare, Link};
+
+/// A violated invariant found by `BinarySearchTree::validate`
+#[derive(Debug, PartialEq, Eq)]
+pub enum ValidationError<'a, T> {
+    /// `item` is in the left subtree of `ancestor`, but is not less than it
+    NotLessThanAncestor { item: &'a T, ancestor: &'a T },
+    /// `item` is in the right subtree of `ancestor`, but is not greater than it
+    NotGreaterThanAncestor { item: &'a T, ancestor: &'a T },
+    /// The size stored in the node holding `item` differs from the size of its subtree
+    WrongSize {
+        item: &'a T,
+        stored: usize,
+        actual: usize,
+    },
+}
+
+impl<'a, T: fmt::Debug> fmt::Display for ValidationError<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::NotLessThanAncestor { item, ancestor } => write!(
+                f,
+                "{:?} is in the left subtree of {:?} but is not less than it",
+                item, ancestor
+            ),
+            ValidationError::NotGreaterThanAncestor { item, ancestor } => write!(
+                f,
+                "{:?} is in the right subtree of {:?} but is not greater than it",
+                item, ancestor
+            ),
+            ValidationError::WrongSize {
+                item,
+                stored,
+                actual,
+            } => write!(
+                f,
+                "Node {:?} stores size {} but its subtree has {} items",
+                item, stored, actual
+            ),
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> Error for ValidationError<'a, T> {}
+
+/// Depth statistics of a non-empty `BinarySearchTree`, where the root has depth 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthStats {
+    /// Number of nodes without children
+    pub leaves: usize,
+    pub max_leaf_depth: usize,
+    pub average_leaf_depth: f64,
+    /// Average depth over all nodes, which is the average cost of a successful search
+    pub average_depth: f64,
+}
+
+impl<T, C: Compare<T>> BinarySearchTree<T, C> {
+    /// Checks the ordering invariant and the stored subtree sizes
+    ///
+    /// Every item must be greater than all items in its left subtree and less
+    /// than all items in its right subtree. The first violation found is
+    /// returned, naming the offending node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::binary_search_tree::BinarySearchTree;
+    ///
+    /// let mut bst = BinarySearchTree::new();
+    /// for i in &[5, 2, 8, 1] {
+    ///     bst.insert(*i);
+    /// }
+    /// bst.delete(5);
+    /// assert_eq!(bst.validate(), Ok(()));
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError<'_, T>> {
+        // The closest ancestors that the subtree lies to the right and to the left of
+        let mut stack: Vec<(&Link<T>, Option<&T>, Option<&T>)> = vec![(&self.root, None, None)];
+        while let Some((link, lower, upper)) = stack.pop() {
+            let node = match link {
+                Some(ref node) => node,
+                None => continue,
+            };
+            if let Some(ancestor) = lower.filter(|ancestor| {
+                self.comparator.compare(&node.item, ancestor) != Ordering::Greater
+            }) {
+                return Err(ValidationError::NotGreaterThanAncestor {
+                    item: &node.item,
+                    ancestor,
+                });
+            }
+            if let Some(ancestor) = upper
+                .filter(|ancestor| self.comparator.compare(&node.item, ancestor) != Ordering::Less)
+            {
+                return Err(ValidationError::NotLessThanAncestor {
+                    item: &node.item,
+                    ancestor,
+                });
+            }
+            stack.push((&node.right, Some(&node.item), upper));
+            stack.push((&node.left, lower, Some(&node.item)));
+        }
+
+        // Visits subtrees in post-order, keeping the sizes of visited subtrees on a stack
+        let mut stack = vec![(&self.root, false)];
+        let mut sizes = Vec::new();
+        while let Some((link, expanded)) = stack.pop() {
+            let node = match link {
+                Some(ref node) => node,
+                None => {
+                    sizes.push(0);
+                    continue;
+                }
+            };
+            if expanded {
+                let right = sizes.pop().unwrap();
+                let left = sizes.pop().unwrap();
+                let actual = 1 + left + right;
+                if node.size != actual {
+                    return Err(ValidationError::WrongSize {
+                        item: &node.item,
+                        stored: node.size,
+                        actual,
+                    });
+                }
+                sizes.push(actual);
+            } else {
+                stack.push((link, true));
+                stack.push((&node.right, false));
+                stack.push((&node.left, false));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of nodes on the longest path from the root to a leaf
+    pub fn height(&self) -> usize {
+        self.depth_stats()
+            .map_or(0, |stats| stats.max_leaf_depth + 1)
+    }
+
+    /// Returns statistics about the depths of the nodes, or `None` for an empty tree
+    pub fn depth_stats(&self) -> Option<DepthStats> {
+        if self.is_empty() {
+            return None;
+        }
+        let (mut leaves, mut max_leaf_depth, mut total_leaf_depth, mut total_depth) = (0, 0, 0, 0);
+        let mut stack = vec![(self.root.as_deref().unwrap(), 0)];
+        while let Some((node, depth)) = stack.pop() {
+            total_depth += depth;
+            if node.left.is_none() && node.right.is_none() {
+                leaves += 1;
+                max_leaf_depth = max_leaf_depth.max(depth);
+                total_leaf_depth += depth;
+            }
+            for child in node.left.iter().chain(node.right.iter()) {
+                stack.push((child, depth + 1));
+            }
+        }
+        Some(DepthStats {
+            leaves,
+            max_leaf_depth,
+            average_leaf_depth: total_leaf_depth as f64 / leaves as f64,
+            average_depth: total_depth as f64 / self.len() as f64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(items: &[i32]) -> BinarySearchTree<i32> {
+        let mut bst = BinarySearchTree::new();
+        for item in items {
+            bst.insert(*item);
+        }
+        bst
+    }
+
+    #[test]
+    fn valid_trees() {
+        assert_eq!(tree(&[]).validate(), Ok(()));
+        assert_eq!(tree(&[5, 2, 8, 1, 3, 7, 9]).validate(), Ok(()));
+        assert_eq!(tree(&(0..10_000).collect::<Vec<_>>()).validate(), Ok(()));
+    }
+
+    #[test]
+    fn item_not_less_than_ancestor() {
+        //     5
+        //    /
+        //   2
+        //    \
+        //     3 -> 6
+        let mut bst = tree(&[5, 2, 3]);
+        let two = bst.root.as_mut().unwrap().left.as_mut().unwrap();
+        two.right.as_mut().unwrap().item = 6;
+
+        let error = bst.validate().unwrap_err();
+        assert_eq!(
+            error,
+            ValidationError::NotLessThanAncestor {
+                item: &6,
+                ancestor: &5
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "6 is in the left subtree of 5 but is not less than it"
+        );
+    }
+
+    #[test]
+    fn item_not_greater_than_ancestor() {
+        let mut bst = tree(&[5, 8, 7]);
+        let eight = bst.root.as_mut().unwrap().right.as_mut().unwrap();
+        eight.left.as_mut().unwrap().item = 5;
+
+        assert_eq!(
+            bst.validate(),
+            Err(ValidationError::NotGreaterThanAncestor {
+                item: &5,
+                ancestor: &5
+            })
+        );
+    }
+
+    #[test]
+    fn wrong_size() {
+        let mut bst = tree(&[5, 2, 8, 1]);
+        bst.root.as_mut().unwrap().left.as_mut().unwrap().size = 3;
+
+        assert_eq!(
+            bst.validate(),
+            Err(ValidationError::WrongSize {
+                item: &2,
+                stored: 3,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn deletes_keep_tree_valid() {
+        let items: Vec<_> = (0..500).map(|i| (i * 37) % 499).collect();
+        let mut bst = tree(&items);
+        for (i, item) in items.iter().enumerate().filter(|(i, _)| i % 3 != 0) {
+            bst.delete(*item);
+            if i % 10 == 1 {
+                assert_eq!(bst.validate(), Ok(()));
+            }
+        }
+        assert_eq!(bst.validate(), Ok(()));
+        assert_eq!(bst.len(), (0..500).filter(|i| i % 3 == 0).count() - 1);
+    }
+
+    #[test]
+    fn height_and_depth_stats() {
+        assert_eq!(tree(&[]).height(), 0);
+        assert_eq!(tree(&[]).depth_stats(), None);
+
+        //       4
+        //     /   \
+        //    2     6
+        //   /     / \
+        //  1     5   7
+        //             \
+        //              8
+        let bst = tree(&[4, 2, 6, 1, 5, 7, 8]);
+        assert_eq!(bst.height(), 4);
+        assert_eq!(
+            bst.depth_stats(),
+            Some(DepthStats {
+                leaves: 3,
+                max_leaf_depth: 3,
+                average_leaf_depth: 7.0 / 3.0,
+                average_depth: 11.0 / 7.0,
+            })
+        );
+    }
+
+    #[test]
+    fn height_of_degenerate_tree() {
+        let bst = tree(&(0..1000).collect::<Vec<_>>());
+        assert_eq!(bst.height(), 1000);
+        assert_eq!(bst.depth_stats().unwrap().leaves, 1);
+    }
+}
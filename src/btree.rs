@@ -0,0 +1,625 @@
+use std::borrow::Borrow;
+use std::iter::FusedIterator;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::ptr;
+
+mod disk;
+mod pager;
+
+pub use self::disk::{DiskBPlusTree, DiskRange, DEFAULT_POOL_PAGES};
+pub use self::pager::PAGE_SIZE;
+
+/// The order used by `BTree::new`
+pub const DEFAULT_ORDER: usize = 16;
+
+struct Node<T> {
+    // Sorted items, separating the subtrees in `children`
+    items: Vec<T>,
+    // Either empty, for a leaf, or one more than the number of items
+    children: Vec<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn leaf() -> Self {
+        Node {
+            items: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+enum Insertion<T> {
+    Present,
+    Inserted,
+    // The node overflowed and was split, leaving the median to be inserted in the parent
+    Split(T, Node<T>),
+}
+
+/// A B-tree, storing many items in each node
+///
+/// Each node holds up to `order - 1` sorted items and, unless it is a leaf,
+/// one child more than it has items. Every node but the root is at least half
+/// full and all leaves are at the same depth, so the height is
+/// O(log n / log order). Since a whole node is searched at a time, a larger
+/// order means fewer nodes to visit, each of which is a contiguous array of
+/// items.
+///
+/// Offers the same set operations as `BinarySearchTree`: equal items are
+/// stored once, and lookups can use any borrowed form of the items.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::btree::BTree;
+///
+/// let mut tree = BTree::with_order(4);
+/// for i in 0..100 {
+///     tree.insert(i);
+/// }
+/// tree.delete(50);
+/// assert!(!tree.contains(&50));
+/// assert_eq!(tree.range(48..53).copied().collect::<Vec<_>>(), vec![48, 49, 51, 52]);
+/// assert!(tree.height() <= 7);
+/// ```
+pub struct BTree<T: Ord> {
+    root: Node<T>,
+    order: usize,
+    len: usize,
+}
+
+impl<T: Ord> Default for BTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> BTree<T> {
+    /// Creates an empty tree of order `DEFAULT_ORDER`
+    pub fn new() -> Self {
+        Self::with_order(DEFAULT_ORDER)
+    }
+
+    /// Creates an empty tree whose nodes have at most `order` children
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is less than 3.
+    pub fn with_order(order: usize) -> Self {
+        assert!(order >= 3, "Order must be at least 3");
+        Self {
+            root: Node::leaf(),
+            order,
+            len: 0,
+        }
+    }
+
+    /// Returns the maximum number of children of a node
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Returns the number of items in the tree
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of levels of nodes, which is zero for an empty tree
+    pub fn height(&self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let mut height = 1;
+        let mut node = &self.root;
+        while let Some(child) = node.children.first() {
+            height += 1;
+            node = child;
+        }
+        height
+    }
+
+    /// Fewest items a node other than the root may hold
+    fn min_items(&self) -> usize {
+        (self.order - 1) / 2
+    }
+
+    /// Returns the stored item equal to `item`
+    pub fn get<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = &self.root;
+        loop {
+            match node
+                .items
+                .binary_search_by(|probe| probe.borrow().cmp(item))
+            {
+                Ok(i) => return Some(&node.items[i]),
+                Err(_) if node.is_leaf() => return None,
+                Err(i) => node = &node.children[i],
+            }
+        }
+    }
+
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(item).is_some()
+    }
+
+    /// Returns the smallest item
+    pub fn min(&self) -> Option<&T> {
+        let mut node = &self.root;
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+        node.items.first()
+    }
+
+    /// Returns the largest item
+    pub fn max(&self) -> Option<&T> {
+        let mut node = &self.root;
+        while let Some(child) = node.children.last() {
+            node = child;
+        }
+        node.items.last()
+    }
+
+    pub fn insert(&mut self, item: T) {
+        match Self::insert_into(&mut self.root, item, self.order) {
+            Insertion::Present => return,
+            Insertion::Inserted => {}
+            Insertion::Split(median, right) => {
+                // The root splits, so the tree grows by one level
+                let left = mem::replace(&mut self.root, Node::leaf());
+                self.root.items.push(median);
+                self.root.children.push(left);
+                self.root.children.push(right);
+            }
+        }
+        self.len += 1;
+    }
+
+    fn insert_into(node: &mut Node<T>, item: T, order: usize) -> Insertion<T> {
+        let i = match node.items.binary_search(&item) {
+            Ok(_) => return Insertion::Present,
+            Err(i) => i,
+        };
+        if node.is_leaf() {
+            node.items.insert(i, item);
+        } else {
+            match Self::insert_into(&mut node.children[i], item, order) {
+                Insertion::Split(median, right) => {
+                    node.items.insert(i, median);
+                    node.children.insert(i + 1, right);
+                }
+                insertion => return insertion,
+            }
+        }
+
+        if node.items.len() < order {
+            return Insertion::Inserted;
+        }
+        let middle = node.items.len() / 2;
+        let right = Node {
+            items: node.items.split_off(middle + 1),
+            children: if node.is_leaf() {
+                Vec::new()
+            } else {
+                node.children.split_off(middle + 1)
+            },
+        };
+        let median = node.items.pop().unwrap();
+        Insertion::Split(median, right)
+    }
+
+    pub fn delete(&mut self, item: T) {
+        self.remove(&item);
+    }
+
+    /// Removes the item equal to `item` and returns it
+    pub fn remove<Q>(&mut self, item: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let min_items = self.min_items();
+        let removed = Self::remove_from(&mut self.root, item, min_items)?;
+        if self.root.items.is_empty() && !self.root.is_leaf() {
+            // The root lost its last item to a merge, so the tree shrinks by one level
+            self.root = self.root.children.pop().unwrap();
+        }
+        self.len -= 1;
+        Some(removed)
+    }
+
+    fn remove_from<Q>(node: &mut Node<T>, item: &Q, min_items: usize) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (i, removed) = match node
+            .items
+            .binary_search_by(|probe| probe.borrow().cmp(item))
+        {
+            Ok(i) if node.is_leaf() => return Some(node.items.remove(i)),
+            Ok(i) => {
+                // Replace the item with its predecessor, the largest item of the left subtree
+                let predecessor = Self::remove_max(&mut node.children[i], min_items);
+                (i, mem::replace(&mut node.items[i], predecessor))
+            }
+            Err(_) if node.is_leaf() => return None,
+            Err(i) => (
+                i,
+                Self::remove_from(&mut node.children[i], item, min_items)?,
+            ),
+        };
+        Self::fix_underflow(node, i, min_items);
+        Some(removed)
+    }
+
+    fn remove_max(node: &mut Node<T>, min_items: usize) -> T {
+        if node.is_leaf() {
+            return node.items.pop().unwrap();
+        }
+        let last = node.children.len() - 1;
+        let max = Self::remove_max(&mut node.children[last], min_items);
+        Self::fix_underflow(node, last, min_items);
+        max
+    }
+
+    /// Refills child `i` if it has too few items, by borrowing from or merging with a sibling
+    fn fix_underflow(node: &mut Node<T>, i: usize, min_items: usize) {
+        if node.children[i].items.len() >= min_items {
+            return;
+        }
+        if i > 0 && node.children[i - 1].items.len() > min_items {
+            // Rotate the largest item of the left sibling through the parent
+            let (left, right) = node.children.split_at_mut(i);
+            let (left, child) = (&mut left[i - 1], &mut right[0]);
+            let separator = mem::replace(&mut node.items[i - 1], left.items.pop().unwrap());
+            child.items.insert(0, separator);
+            if let Some(grandchild) = left.children.pop() {
+                child.children.insert(0, grandchild);
+            }
+        } else if i + 1 < node.children.len() && node.children[i + 1].items.len() > min_items {
+            // Rotate the smallest item of the right sibling through the parent
+            let (left, right) = node.children.split_at_mut(i + 1);
+            let (child, right) = (&mut left[i], &mut right[0]);
+            let separator = mem::replace(&mut node.items[i], right.items.remove(0));
+            child.items.push(separator);
+            if !right.is_leaf() {
+                child.children.push(right.children.remove(0));
+            }
+        } else {
+            // Both siblings are minimal, so merge with one of them and the separator between
+            let i = i.saturating_sub(1);
+            let right = node.children.remove(i + 1);
+            let separator = node.items.remove(i);
+            let left = &mut node.children[i];
+            left.items.push(separator);
+            left.items.extend(right.items);
+            left.children.extend(right.children);
+        }
+    }
+
+    /// Returns an iterator over the items in sorted order
+    pub fn iter(&self) -> Range<'_, T> {
+        self.range::<T, _>(..)
+    }
+
+    /// Returns an iterator over the items inside `range`, in sorted order
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut front = Vec::new();
+        let mut node = &self.root;
+        loop {
+            let i = match range.start_bound() {
+                Bound::Included(start) => node.items.partition_point(|x| x.borrow() < start),
+                Bound::Excluded(start) => node.items.partition_point(|x| x.borrow() <= start),
+                Bound::Unbounded => 0,
+            };
+            front.push((node, i));
+            match node.children.get(i) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+
+        let mut back = Vec::new();
+        let mut node = &self.root;
+        loop {
+            let i = match range.end_bound() {
+                Bound::Included(end) => node.items.partition_point(|x| x.borrow() <= end),
+                Bound::Excluded(end) => node.items.partition_point(|x| x.borrow() < end),
+                Bound::Unbounded => node.items.len(),
+            };
+            back.push((node, i));
+            match node.children.get(i) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+
+        let mut range = Range {
+            front,
+            back,
+            remaining: None,
+        };
+        if let (Some(first), Some(last)) = (range.advance_front(), range.advance_back()) {
+            if first <= last {
+                range.remaining = Some((first, last));
+            }
+        }
+        range
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a BTree<T> {
+    type Item = &'a T;
+    type IntoIter = Range<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An in-order iterator over the items of a `BTree`, or the items inside a range
+pub struct Range<'a, T> {
+    // Path to the next item from the front. Each node is paired with the index of its next item.
+    front: Vec<(&'a Node<T>, usize)>,
+    // Path to the next item from the back. Each node is paired with the number of items left before the back.
+    back: Vec<(&'a Node<T>, usize)>,
+    // The first and last items not yet returned
+    remaining: Option<(&'a T, &'a T)>,
+}
+
+impl<'a, T> Range<'a, T> {
+    fn advance_front(&mut self) -> Option<&'a T> {
+        loop {
+            let (node, i) = self.front.last_mut()?;
+            let node: &'a Node<T> = node;
+            if *i == node.items.len() {
+                self.front.pop();
+                continue;
+            }
+            let item = &node.items[*i];
+            *i += 1;
+            let mut child = node.children.get(*i);
+            while let Some(node) = child {
+                self.front.push((node, 0));
+                child = node.children.first();
+            }
+            return Some(item);
+        }
+    }
+
+    fn advance_back(&mut self) -> Option<&'a T> {
+        loop {
+            let (node, i) = self.back.last_mut()?;
+            let node: &'a Node<T> = node;
+            if *i == 0 {
+                self.back.pop();
+                continue;
+            }
+            *i -= 1;
+            let item = &node.items[*i];
+            let mut child = node.children.get(*i);
+            while let Some(node) = child {
+                self.back.push((node, node.items.len()));
+                child = node.children.last();
+            }
+            return Some(item);
+        }
+    }
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (first, last) = self.remaining?;
+        self.remaining = if ptr::eq(first, last) {
+            None
+        } else {
+            self.advance_front().map(|next| (next, last))
+        };
+        Some(first)
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Range<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (first, last) = self.remaining?;
+        self.remaining = if ptr::eq(first, last) {
+            None
+        } else {
+            self.advance_back().map(|previous| (first, previous))
+        };
+        Some(last)
+    }
+}
+
+impl<'a, T> FusedIterator for Range<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary_search_tree::BinarySearchTree;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Checks the B-tree invariants, returning the depth of the leaves below `node`
+    fn check_node<T: Ord>(node: &Node<T>, order: usize, is_root: bool) -> usize {
+        assert!(node.items.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(node.items.len() < order, "Node has too many items");
+        if !is_root {
+            assert!(node.items.len() >= (order - 1) / 2, "Node is underfull");
+        }
+        if node.is_leaf() {
+            return 0;
+        }
+        assert_eq!(node.children.len(), node.items.len() + 1);
+        let depths: Vec<_> = node
+            .children
+            .iter()
+            .enumerate()
+            .map(|(i, child)| {
+                if i > 0 {
+                    assert!(child.items[0] > node.items[i - 1]);
+                }
+                if i < node.items.len() {
+                    assert!(*child.items.last().unwrap() < node.items[i]);
+                }
+                check_node(child, order, false)
+            })
+            .collect();
+        assert!(
+            depths.windows(2).all(|pair| pair[0] == pair[1]),
+            "Leaves are at different depths"
+        );
+        depths[0] + 1
+    }
+
+    fn check<T: Ord>(tree: &BTree<T>) {
+        check_node(&tree.root, tree.order, true);
+        assert_eq!(tree.iter().count(), tree.len());
+    }
+
+    fn bounds(rng: &mut StdRng) -> (Bound<u32>, Bound<u32>) {
+        let mut bound = || match rng.gen_range(0, 3) {
+            0 => Bound::Included(rng.gen_range(0, 200)),
+            1 => Bound::Excluded(rng.gen_range(0, 200)),
+            _ => Bound::Unbounded,
+        };
+        (bound(), bound())
+    }
+
+    #[test]
+    fn empty_tree() {
+        let mut tree: BTree<i32> = BTree::new();
+        assert!(tree.is_empty());
+        assert_eq!(tree.height(), 0);
+        assert_eq!(tree.iter().next(), None);
+        assert_eq!(tree.remove(&1), None);
+        assert_eq!((tree.min(), tree.max()), (None, None));
+    }
+
+    #[test]
+    #[should_panic(expected = "Order must be at least 3")]
+    fn order_below_three_panics() {
+        BTree::<i32>::with_order(2);
+    }
+
+    #[test]
+    fn sorted_inserts_stay_shallow() {
+        let mut tree = BTree::with_order(3);
+        for i in 0..1023 {
+            tree.insert(i);
+        }
+        check(&tree);
+        assert!(tree.height() <= 10);
+        assert_eq!((tree.min(), tree.max()), (Some(&0), Some(&1022)));
+    }
+
+    #[test]
+    fn delete_everything_in_every_order() {
+        for order in 3..8 {
+            let mut tree = BTree::with_order(order);
+            for i in 0..200 {
+                tree.insert((i * 37) % 200);
+            }
+            for i in 0..200 {
+                assert_eq!(tree.remove(&((i * 53) % 200)), Some((i * 53) % 200));
+                check(&tree);
+            }
+            assert!(tree.is_empty());
+            assert_eq!(tree.height(), 0);
+        }
+    }
+
+    #[test]
+    fn iterate_from_both_ends() {
+        let mut tree = BTree::with_order(4);
+        for i in 0..50 {
+            tree.insert(i);
+        }
+        let mut iter = tree.iter();
+        let mut items = Vec::new();
+        while let Some(item) = iter.next() {
+            items.push(*item);
+            if let Some(item) = iter.next_back() {
+                items.push(*item);
+            }
+        }
+        items.sort_unstable();
+        assert_eq!(items, (0..50).collect::<Vec<_>>());
+        assert!(tree
+            .iter()
+            .rev()
+            .eq((0..50).rev().collect::<Vec<_>>().iter()));
+    }
+
+    #[test]
+    fn lookups_by_borrowed_form() {
+        let mut tree = BTree::with_order(3);
+        for word in &["b", "a", "c", "d"] {
+            tree.insert(word.to_string());
+        }
+        assert!(tree.contains("c"));
+        assert_eq!(
+            tree.range::<str, _>((Bound::Included("b"), Bound::Included("c")))
+                .count(),
+            2
+        );
+        assert_eq!(tree.remove("a"), Some(String::from("a")));
+    }
+
+    /// Random operations give the same results as on a `BinarySearchTree`, for many orders and seeds
+    #[test]
+    fn matches_binary_search_tree() {
+        for order in &[3, 4, 5, 8, 16, 33] {
+            for seed in 0..10 {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut tree = BTree::with_order(*order);
+                let mut bst = BinarySearchTree::new();
+                for _ in 0..1000 {
+                    let item: u32 = rng.gen_range(0, 200);
+                    match rng.gen_range(0, 4) {
+                        0 | 1 => {
+                            tree.insert(item);
+                            bst.insert(item);
+                        }
+                        2 => assert_eq!(tree.remove(&item), bst.remove(&item)),
+                        _ => assert_eq!(tree.contains(&item), bst.contains(&item)),
+                    }
+                    assert_eq!(tree.len(), bst.len());
+                }
+                check(&tree);
+                assert!(tree.iter().eq(bst.iter()));
+                assert_eq!(tree.min(), bst.min());
+                assert_eq!(tree.max(), bst.max());
+                for _ in 0..50 {
+                    let range = bounds(&mut rng);
+                    assert!(tree.range(range).eq(bst.range(range)), "range {:?}", range);
+                    assert!(tree.range(range).rev().eq(bst.range(range).rev()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,1062 @@
+use super::pager::{Page, PageId, Pager, PAGE_SIZE};
+use std::collections::HashSet;
+use std::convert::TryInto;
+use std::fs::OpenOptions;
+use std::io;
+use std::iter::FusedIterator;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+
+/// The number of pages cached by `DiskBPlusTree::open`
+pub const DEFAULT_POOL_PAGES: usize = 256;
+
+const MAGIC: u64 = u64::from_le_bytes(*b"BPTREE01");
+
+// Pages 0 and 1 hold the two most recent commits, alternately
+const META_PAGES: u64 = 2;
+
+// Marks the absence of a root, since page 0 is never a node
+const NO_PAGE: PageId = 0;
+
+const LEAF: u8 = 1;
+const INTERNAL: u8 = 2;
+const HEADER_SIZE: usize = 8;
+const LEAF_CAPACITY: usize = (PAGE_SIZE - HEADER_SIZE) / 16;
+const INTERNAL_CAPACITY: usize = (PAGE_SIZE - HEADER_SIZE - 8) / 16;
+const CHILDREN_OFFSET: usize = HEADER_SIZE + 8 * INTERNAL_CAPACITY;
+
+// Fewest pairs or keys a node other than the root holds after a removal
+const MIN_LEAF_ENTRIES: usize = LEAF_CAPACITY / 2;
+const MIN_INTERNAL_KEYS: usize = INTERNAL_CAPACITY / 2;
+
+fn read_u64(page: &Page, offset: usize) -> u64 {
+    u64::from_le_bytes(page[offset..offset + 8].try_into().unwrap())
+}
+
+fn write_u64(page: &mut Page, offset: usize, value: u64) {
+    page[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+/// FNV-1a hash, enough to tell a torn meta page from a whole one
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn corrupt(id: PageId) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Page {} is corrupt", id),
+    )
+}
+
+#[derive(Clone, Copy)]
+struct Meta {
+    sequence: u64,
+    root: PageId,
+    len: u64,
+}
+
+impl Meta {
+    fn encode(&self) -> Page {
+        let mut page = [0; PAGE_SIZE];
+        write_u64(&mut page, 0, MAGIC);
+        write_u64(&mut page, 8, self.sequence);
+        write_u64(&mut page, 16, self.root);
+        write_u64(&mut page, 24, self.len);
+        let sum = checksum(&page[..32]);
+        write_u64(&mut page, 32, sum);
+        page
+    }
+
+    fn decode(page: &Page) -> Option<Self> {
+        if read_u64(page, 0) != MAGIC || read_u64(page, 32) != checksum(&page[..32]) {
+            return None;
+        }
+        Some(Meta {
+            sequence: read_u64(page, 8),
+            root: read_u64(page, 16),
+            len: read_u64(page, 24),
+        })
+    }
+}
+
+enum Node {
+    // Sorted key-value pairs
+    Leaf(Vec<(u64, u64)>),
+    // The keys of `children[i]` are at least `keys[i - 1]` and less than `keys[i]`
+    Internal {
+        keys: Vec<u64>,
+        children: Vec<PageId>,
+    },
+}
+
+impl Node {
+    fn encode(&self) -> Page {
+        let mut page = [0; PAGE_SIZE];
+        match self {
+            Node::Leaf(entries) => {
+                page[0] = LEAF;
+                page[2..4].copy_from_slice(&(entries.len() as u16).to_le_bytes());
+                for (i, &(key, value)) in entries.iter().enumerate() {
+                    write_u64(&mut page, HEADER_SIZE + 16 * i, key);
+                    write_u64(&mut page, HEADER_SIZE + 16 * i + 8, value);
+                }
+            }
+            Node::Internal { keys, children } => {
+                page[0] = INTERNAL;
+                page[2..4].copy_from_slice(&(keys.len() as u16).to_le_bytes());
+                for (i, &key) in keys.iter().enumerate() {
+                    write_u64(&mut page, HEADER_SIZE + 8 * i, key);
+                }
+                for (i, &child) in children.iter().enumerate() {
+                    write_u64(&mut page, CHILDREN_OFFSET + 8 * i, child);
+                }
+            }
+        }
+        page
+    }
+
+    fn decode(page: &Page) -> Option<Self> {
+        let count = usize::from(u16::from_le_bytes([page[2], page[3]]));
+        match page[0] {
+            LEAF if count <= LEAF_CAPACITY => Some(Node::Leaf(
+                (0..count)
+                    .map(|i| {
+                        let offset = HEADER_SIZE + 16 * i;
+                        (read_u64(page, offset), read_u64(page, offset + 8))
+                    })
+                    .collect(),
+            )),
+            INTERNAL if count <= INTERNAL_CAPACITY => Some(Node::Internal {
+                keys: (0..count)
+                    .map(|i| read_u64(page, HEADER_SIZE + 8 * i))
+                    .collect(),
+                children: (0..=count)
+                    .map(|i| read_u64(page, CHILDREN_OFFSET + 8 * i))
+                    .collect(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+// A new page and the separator before it, left by a node that overflowed and was split
+type Split = Option<(u64, PageId)>;
+
+/// A B+-tree mapping `u64` keys to `u64` values, stored in a file of fixed-size pages
+///
+/// All pairs are kept in the leaves. Internal nodes only hold separator keys,
+/// so that each page fans out to up to 256 children. Pages are accessed
+/// through a buffer pool holding a bounded number of them in memory, which is
+/// why lookups need a mutable reference.
+///
+/// Changes are not durable until `commit` is called, and are lost if the
+/// tree is dropped before then. Pages of the committed tree are never
+/// overwritten: a change copies the pages on the path from the leaf to the
+/// root, and a commit syncs the copies before switching to the new root.
+/// The root is recorded in one of two meta pages, alternately, along with a
+/// sequence number and a checksum. On opening, the most recent meta page that
+/// is intact is used, so a crash or a torn write at any point leaves the
+/// last committed tree, or at worst the one before it, readable. Pages not
+/// reachable from the root are found again on opening and reused.
+///
+/// Leaves are not linked to their siblings, since copying a leaf would then
+/// mean copying its neighbors too. Range scans instead keep the path from the
+/// root, and move from one leaf to the next through their parents.
+///
+/// Removal keeps every node other than the root at least half full, by
+/// merging a node that falls below that with a sibling, or moving pairs over
+/// from the sibling if the two do not fit in one page. Freed pages are reused,
+/// but the file never shrinks.
+///
+/// Keys and values are fixed at `u64`, so that every pair takes the same
+/// space in a page.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::btree::DiskBPlusTree;
+///
+/// let path = std::env::temp_dir().join(format!("doctest-{}.db", std::process::id()));
+/// # let _ = std::fs::remove_file(&path);
+/// let mut tree = DiskBPlusTree::open(&path)?;
+/// for key in 0..1000 {
+///     tree.insert(key, key * key)?;
+/// }
+/// tree.commit()?;
+/// drop(tree);
+///
+/// let mut tree = DiskBPlusTree::open(&path)?;
+/// assert_eq!(tree.len(), 1000);
+/// assert_eq!(tree.get(30)?, Some(900));
+/// let squares = tree.range(10..13).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(squares, vec![(10, 100), (11, 121), (12, 144)]);
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct DiskBPlusTree {
+    pager: Pager,
+    root: PageId,
+    len: u64,
+    // Sequence number of the last commit
+    sequence: u64,
+    // Number of pages in use or free, including the meta pages
+    page_count: u64,
+    // Pages allocated since the last commit, which can be changed in place
+    fresh: HashSet<PageId>,
+    // Pages the committed tree no longer uses, fine to overwrite
+    free: Vec<PageId>,
+    // Pages dropped since the last commit, still used by the committed tree
+    released: Vec<PageId>,
+    dirty: bool,
+}
+
+impl DiskBPlusTree {
+    /// Opens the tree stored in a file, or creates an empty one if the file does not exist
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::with_pool_size(path, DEFAULT_POOL_PAGES)
+    }
+
+    /// Opens the tree stored in a file, caching up to `pool_pages` pages in memory
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pool_pages` is zero.
+    pub fn with_pool_size<P: AsRef<Path>>(path: P, pool_pages: usize) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let is_new = file.metadata()?.len() == 0;
+        let mut pager = Pager::new(file, pool_pages);
+
+        let meta = if is_new {
+            let meta = Meta {
+                sequence: 0,
+                root: NO_PAGE,
+                len: 0,
+            };
+            pager.write_direct(0, &meta.encode())?;
+            meta
+        } else {
+            let mut latest: Option<Meta> = None;
+            let mut page = [0; PAGE_SIZE];
+            for id in 0..META_PAGES {
+                pager.read_direct(id, &mut page)?;
+                if let Some(meta) = Meta::decode(&page) {
+                    if latest.map_or(true, |latest| meta.sequence > latest.sequence) {
+                        latest = Some(meta);
+                    }
+                }
+            }
+            latest
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "No intact meta page"))?
+        };
+
+        let mut tree = DiskBPlusTree {
+            page_count: pager.file_pages()?.max(META_PAGES),
+            pager,
+            root: meta.root,
+            len: meta.len,
+            sequence: meta.sequence,
+            fresh: HashSet::new(),
+            free: Vec::new(),
+            released: Vec::new(),
+            dirty: false,
+        };
+        tree.collect_free_pages()?;
+        Ok(tree)
+    }
+
+    /// Marks every page not reachable from the root as free
+    ///
+    /// These are either dropped by a commit, or written by changes that
+    /// were never committed.
+    fn collect_free_pages(&mut self) -> io::Result<()> {
+        let mut used = HashSet::new();
+        let mut stack = Vec::new();
+        if self.root != NO_PAGE {
+            stack.push(self.root);
+        }
+        while let Some(id) = stack.pop() {
+            if id < META_PAGES || id >= self.page_count || !used.insert(id) {
+                return Err(corrupt(id));
+            }
+            if let Node::Internal { children, .. } = self.read_node(id)? {
+                stack.extend(children);
+            }
+        }
+        // Reversed, so the lowest pages are reused first
+        self.free = (META_PAGES..self.page_count)
+            .rev()
+            .filter(|id| !used.contains(id))
+            .collect();
+        Ok(())
+    }
+
+    /// Returns the number of pairs in the tree
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Returns `true` if the tree has no pairs
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the value for a key
+    pub fn get(&mut self, key: u64) -> io::Result<Option<u64>> {
+        if self.root == NO_PAGE {
+            return Ok(None);
+        }
+        let mut id = self.root;
+        loop {
+            match self.read_node(id)? {
+                Node::Leaf(entries) => {
+                    return Ok(entries
+                        .binary_search_by_key(&key, |entry| entry.0)
+                        .ok()
+                        .map(|i| entries[i].1));
+                }
+                Node::Internal { keys, children } => {
+                    id = children[keys.partition_point(|&separator| separator <= key)];
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the tree has a value for a key
+    pub fn contains_key(&mut self, key: u64) -> io::Result<bool> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    /// Sets the value for a key, returning the previous value
+    pub fn insert(&mut self, key: u64, value: u64) -> io::Result<Option<u64>> {
+        if self.root == NO_PAGE {
+            self.root = self.write_new(&Node::Leaf(vec![(key, value)]))?;
+            self.len = 1;
+            return Ok(None);
+        }
+        let (root, split, previous) = self.insert_at(self.root, key, value)?;
+        self.root = root;
+        if let Some((separator, right)) = split {
+            self.root = self.write_new(&Node::Internal {
+                keys: vec![separator],
+                children: vec![root, right],
+            })?;
+        }
+        if previous.is_none() {
+            self.len += 1;
+        }
+        Ok(previous)
+    }
+
+    /// Inserts a pair below a node, returning the node's new page
+    fn insert_at(
+        &mut self,
+        id: PageId,
+        key: u64,
+        value: u64,
+    ) -> io::Result<(PageId, Split, Option<u64>)> {
+        match self.read_node(id)? {
+            Node::Leaf(mut entries) => {
+                let previous = match entries.binary_search_by_key(&key, |entry| entry.0) {
+                    Ok(i) => Some(mem::replace(&mut entries[i].1, value)),
+                    Err(i) => {
+                        entries.insert(i, (key, value));
+                        None
+                    }
+                };
+                let split = if entries.len() > LEAF_CAPACITY {
+                    let right = entries.split_off(entries.len() / 2);
+                    Some((right[0].0, self.write_new(&Node::Leaf(right))?))
+                } else {
+                    None
+                };
+                Ok((self.write_node(id, &Node::Leaf(entries))?, split, previous))
+            }
+            Node::Internal {
+                mut keys,
+                mut children,
+            } => {
+                let i = keys.partition_point(|&separator| separator <= key);
+                let (child, split, previous) = self.insert_at(children[i], key, value)?;
+                children[i] = child;
+                if let Some((separator, right)) = split {
+                    keys.insert(i, separator);
+                    children.insert(i + 1, right);
+                }
+                let split = if keys.len() > INTERNAL_CAPACITY {
+                    let mid = keys.len() / 2;
+                    let right = Node::Internal {
+                        keys: keys.split_off(mid + 1),
+                        children: children.split_off(mid + 1),
+                    };
+                    let separator = keys.pop().unwrap();
+                    Some((separator, self.write_new(&right)?))
+                } else {
+                    None
+                };
+                let node = Node::Internal { keys, children };
+                Ok((self.write_node(id, &node)?, split, previous))
+            }
+        }
+    }
+
+    /// Removes a key, returning its value
+    pub fn remove(&mut self, key: u64) -> io::Result<Option<u64>> {
+        if self.root == NO_PAGE {
+            return Ok(None);
+        }
+        let (root, _, value) = match self.remove_at(self.root, key)? {
+            Some(removed) => removed,
+            None => return Ok(None),
+        };
+        self.root = root;
+        self.len -= 1;
+        // A root with a single child is replaced by the child, and an empty root leaf by nothing
+        while self.root != NO_PAGE {
+            match self.read_node(self.root)? {
+                Node::Internal { children, .. } if children.len() == 1 => {
+                    self.release(self.root);
+                    self.root = children[0];
+                }
+                Node::Leaf(entries) if entries.is_empty() => {
+                    self.release(self.root);
+                    self.root = NO_PAGE;
+                }
+                _ => break,
+            }
+        }
+        Ok(Some(value))
+    }
+
+    /// Removes a key below a node, returning the node's new page and whether it became underfull
+    fn remove_at(&mut self, id: PageId, key: u64) -> io::Result<Option<(PageId, bool, u64)>> {
+        match self.read_node(id)? {
+            Node::Leaf(mut entries) => {
+                let i = match entries.binary_search_by_key(&key, |entry| entry.0) {
+                    Ok(i) => i,
+                    Err(_) => return Ok(None),
+                };
+                let (_, value) = entries.remove(i);
+                let underfull = entries.len() < MIN_LEAF_ENTRIES;
+                let id = self.write_node(id, &Node::Leaf(entries))?;
+                Ok(Some((id, underfull, value)))
+            }
+            Node::Internal {
+                mut keys,
+                mut children,
+            } => {
+                let i = keys.partition_point(|&separator| separator <= key);
+                let (child, child_underfull, value) = match self.remove_at(children[i], key)? {
+                    Some(removed) => removed,
+                    None => return Ok(None),
+                };
+                children[i] = child;
+                if child_underfull {
+                    self.fix_underflow(&mut keys, &mut children, i)?;
+                }
+                let underfull = keys.len() < MIN_INTERNAL_KEYS;
+                let id = self.write_node(id, &Node::Internal { keys, children })?;
+                Ok(Some((id, underfull, value)))
+            }
+        }
+    }
+
+    /// Refills child `i` of an internal node by merging it with a sibling
+    ///
+    /// If the merged node does not fit in a page, it is split evenly again,
+    /// which amounts to moving pairs or children over from the sibling.
+    fn fix_underflow(
+        &mut self,
+        keys: &mut Vec<u64>,
+        children: &mut Vec<PageId>,
+        i: usize,
+    ) -> io::Result<()> {
+        // Merge with the left sibling, or the right one for the first child
+        let i = i.saturating_sub(1);
+        let (left, right) = (children[i], children[i + 1]);
+        let merged = match (self.read_node(left)?, self.read_node(right)?) {
+            (Node::Leaf(mut entries), Node::Leaf(right_entries)) => {
+                entries.extend(right_entries);
+                Node::Leaf(entries)
+            }
+            (
+                Node::Internal {
+                    keys: mut merged_keys,
+                    children: mut merged_children,
+                },
+                Node::Internal {
+                    keys: right_keys,
+                    children: right_children,
+                },
+            ) => {
+                merged_keys.push(keys[i]);
+                merged_keys.extend(right_keys);
+                merged_children.extend(right_children);
+                Node::Internal {
+                    keys: merged_keys,
+                    children: merged_children,
+                }
+            }
+            _ => return Err(corrupt(right)),
+        };
+        match merged {
+            Node::Leaf(mut entries) if entries.len() > LEAF_CAPACITY => {
+                let right_entries = entries.split_off(entries.len() / 2);
+                keys[i] = right_entries[0].0;
+                children[i] = self.write_node(left, &Node::Leaf(entries))?;
+                children[i + 1] = self.write_node(right, &Node::Leaf(right_entries))?;
+            }
+            Node::Internal {
+                keys: mut merged_keys,
+                children: mut merged_children,
+            } if merged_keys.len() > INTERNAL_CAPACITY => {
+                let mid = merged_keys.len() / 2;
+                let right_node = Node::Internal {
+                    keys: merged_keys.split_off(mid + 1),
+                    children: merged_children.split_off(mid + 1),
+                };
+                keys[i] = merged_keys.pop().unwrap();
+                let left_node = Node::Internal {
+                    keys: merged_keys,
+                    children: merged_children,
+                };
+                children[i] = self.write_node(left, &left_node)?;
+                children[i + 1] = self.write_node(right, &right_node)?;
+            }
+            merged => {
+                children[i] = self.write_node(left, &merged)?;
+                self.release(right);
+                keys.remove(i);
+                children.remove(i + 1);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over the pairs with keys inside a range, in order of their keys
+    pub fn range<R: RangeBounds<u64>>(&mut self, range: R) -> DiskRange<'_> {
+        DiskRange {
+            start: range.start_bound().cloned(),
+            end: range.end_bound().cloned(),
+            tree: self,
+            started: false,
+            done: false,
+            path: Vec::new(),
+            entries: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Returns an iterator over all pairs, in order of their keys
+    pub fn iter(&mut self) -> DiskRange<'_> {
+        self.range(..)
+    }
+
+    /// Makes all changes durable
+    ///
+    /// Changed pages are written and synced before the meta page that refers
+    /// to them, so the previous commit stays intact until this one is.
+    pub fn commit(&mut self) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        self.pager.flush()?;
+        let meta = Meta {
+            sequence: self.sequence + 1,
+            root: self.root,
+            len: self.len,
+        };
+        // Overwrites the older of the two meta pages
+        self.pager
+            .write_direct(meta.sequence % META_PAGES, &meta.encode())?;
+        self.sequence = meta.sequence;
+        // The new commit no longer needs the released pages, and recovery
+        // never goes back further than the commit before it
+        self.free.append(&mut self.released);
+        self.fresh.clear();
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn read_node(&mut self, id: PageId) -> io::Result<Node> {
+        Node::decode(self.pager.read(id)?).ok_or_else(|| corrupt(id))
+    }
+
+    /// Writes a node to a new page
+    fn write_new(&mut self, node: &Node) -> io::Result<PageId> {
+        let id = match self.free.pop() {
+            Some(id) => id,
+            None => {
+                self.page_count += 1;
+                self.page_count - 1
+            }
+        };
+        self.fresh.insert(id);
+        self.dirty = true;
+        self.pager.write(id, &node.encode())?;
+        Ok(id)
+    }
+
+    /// Writes a changed node, copying it to a new page if it is part of the committed tree
+    fn write_node(&mut self, id: PageId, node: &Node) -> io::Result<PageId> {
+        if self.fresh.contains(&id) {
+            self.pager.write(id, &node.encode())?;
+            return Ok(id);
+        }
+        self.release(id);
+        self.write_new(node)
+    }
+
+    /// Marks a page as no longer used by the tree
+    fn release(&mut self, id: PageId) {
+        if self.fresh.remove(&id) {
+            self.free.push(id);
+        } else {
+            self.released.push(id);
+        }
+        self.dirty = true;
+    }
+}
+
+/// An iterator over the pairs of a `DiskBPlusTree` inside a range
+///
+/// Reading a page can fail, in which case the error is returned and the
+/// iteration stops.
+pub struct DiskRange<'a> {
+    tree: &'a mut DiskBPlusTree,
+    start: Bound<u64>,
+    end: Bound<u64>,
+    started: bool,
+    done: bool,
+    // The children of the internal nodes above the current leaf, each paired with the index of the child being visited
+    path: Vec<(Vec<PageId>, usize)>,
+    // Pairs of the current leaf
+    entries: Vec<(u64, u64)>,
+    position: usize,
+}
+
+impl<'a> DiskRange<'a> {
+    /// Descends to the leftmost leaf below a page, or the leaf that would hold `key`
+    fn descend(&mut self, mut id: PageId, key: Option<u64>) -> io::Result<()> {
+        loop {
+            match self.tree.read_node(id)? {
+                Node::Internal { keys, children } => {
+                    let i =
+                        key.map_or(0, |key| keys.partition_point(|&separator| separator <= key));
+                    id = children[i];
+                    self.path.push((children, i));
+                }
+                Node::Leaf(entries) => {
+                    self.entries = entries;
+                    self.position = 0;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn seek(&mut self) -> io::Result<()> {
+        if self.tree.root == NO_PAGE {
+            return Ok(());
+        }
+        let key = match self.start {
+            Bound::Included(key) | Bound::Excluded(key) => Some(key),
+            Bound::Unbounded => None,
+        };
+        self.descend(self.tree.root, key)?;
+        self.position = match self.start {
+            Bound::Included(start) => self.entries.partition_point(|entry| entry.0 < start),
+            Bound::Excluded(start) => self.entries.partition_point(|entry| entry.0 <= start),
+            Bound::Unbounded => 0,
+        };
+        Ok(())
+    }
+
+    fn advance(&mut self) -> io::Result<Option<(u64, u64)>> {
+        if !self.started {
+            self.started = true;
+            self.seek()?;
+        }
+        loop {
+            if let Some(&(key, value)) = self.entries.get(self.position) {
+                self.position += 1;
+                let in_range = match self.end {
+                    Bound::Included(end) => key <= end,
+                    Bound::Excluded(end) => key < end,
+                    Bound::Unbounded => true,
+                };
+                return Ok(if in_range { Some((key, value)) } else { None });
+            }
+            // Climb to the first node with a child right of the path, and descend from that child
+            let next = loop {
+                let (children, i) = match self.path.last_mut() {
+                    Some(last) => last,
+                    None => return Ok(None),
+                };
+                if *i + 1 < children.len() {
+                    *i += 1;
+                    break children[*i];
+                }
+                self.path.pop();
+            };
+            self.descend(next, None)?;
+        }
+    }
+}
+
+impl<'a> Iterator for DiskRange<'a> {
+    type Item = io::Result<(u64, u64)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.advance() {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl<'a> FusedIterator for DiskRange<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
+    use std::env;
+    use std::fs;
+    use std::io::{Seek, SeekFrom, Write};
+    use std::path::PathBuf;
+    use std::process;
+
+    /// A file in the temporary directory, removed when dropped
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let path =
+                env::temp_dir().join(format!("disk-bplus-tree-{}-{}.db", process::id(), name));
+            let _ = fs::remove_file(&path);
+            TempPath(path)
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn collect(tree: &mut DiskBPlusTree) -> Vec<(u64, u64)> {
+        tree.iter().collect::<io::Result<_>>().unwrap()
+    }
+
+    /// Checks that the keys below every node lie between its separators and that nodes are at least half full, returning the number of pages in use
+    fn check(tree: &mut DiskBPlusTree) -> usize {
+        let mut pages = 0;
+        let mut stack = Vec::new();
+        if tree.root != NO_PAGE {
+            stack.push((tree.root, Bound::Unbounded, Bound::Unbounded));
+        }
+        while let Some((id, start, end)) = stack.pop() {
+            pages += 1;
+            let is_root = id == tree.root;
+            match tree.read_node(id).unwrap() {
+                Node::Leaf(entries) => {
+                    assert!(
+                        is_root || entries.len() >= MIN_LEAF_ENTRIES,
+                        "Leaf is underfull"
+                    );
+                    assert!(entries.windows(2).all(|pair| pair[0].0 < pair[1].0));
+                    assert!(entries.iter().all(|entry| (start, end).contains(&entry.0)));
+                }
+                Node::Internal { keys, children } => {
+                    assert!(
+                        is_root || keys.len() >= MIN_INTERNAL_KEYS,
+                        "Node is underfull"
+                    );
+                    assert!(!keys.is_empty() && keys.windows(2).all(|pair| pair[0] < pair[1]));
+                    assert!(keys.iter().all(|key| (start, end).contains(key)));
+                    for (i, child) in children.into_iter().enumerate() {
+                        let child_start = if i == 0 {
+                            start
+                        } else {
+                            Bound::Included(keys[i - 1])
+                        };
+                        let child_end = keys.get(i).map_or(end, |&key| Bound::Excluded(key));
+                        stack.push((child, child_start, child_end));
+                    }
+                }
+            }
+        }
+        pages
+    }
+
+    fn file_pages(path: &TempPath) -> u64 {
+        fs::metadata(&path.0).unwrap().len() / PAGE_SIZE as u64
+    }
+
+    #[test]
+    fn empty_tree() {
+        let path = TempPath::new("empty");
+        let mut tree = DiskBPlusTree::open(&path.0).unwrap();
+        assert!(tree.is_empty());
+        assert_eq!(tree.get(1).unwrap(), None);
+        assert_eq!(tree.remove(1).unwrap(), None);
+        assert!(collect(&mut tree).is_empty());
+        drop(tree);
+
+        let mut tree = DiskBPlusTree::open(&path.0).unwrap();
+        assert!(tree.is_empty());
+        assert!(collect(&mut tree).is_empty());
+    }
+
+    #[test]
+    fn persists_committed_changes() {
+        let path = TempPath::new("persists");
+        let mut tree = DiskBPlusTree::with_pool_size(&path.0, 4).unwrap();
+        for key in 0..70_000 {
+            tree.insert(key, key + 1).unwrap();
+        }
+        for key in (0..70_000).step_by(3) {
+            assert_eq!(tree.remove(key).unwrap(), Some(key + 1));
+        }
+        tree.commit().unwrap();
+        drop(tree);
+
+        let mut tree = DiskBPlusTree::with_pool_size(&path.0, 4).unwrap();
+        assert_eq!(tree.len(), 46_666);
+        assert_eq!(tree.get(1).unwrap(), Some(2));
+        assert_eq!(tree.get(3).unwrap(), None);
+        assert!(tree.contains_key(69_998).unwrap());
+        let expected: Vec<_> = (0..70_000)
+            .filter(|key| key % 3 != 0)
+            .map(|key| (key, key + 1))
+            .collect();
+        assert_eq!(collect(&mut tree), expected);
+    }
+
+    #[test]
+    fn drops_uncommitted_changes() {
+        let path = TempPath::new("uncommitted");
+        let mut tree = DiskBPlusTree::with_pool_size(&path.0, 2).unwrap();
+        for key in 0..1000 {
+            tree.insert(key, key).unwrap();
+        }
+        tree.commit().unwrap();
+        for key in 0..1000 {
+            tree.insert(key, 0).unwrap();
+        }
+        for key in 1000..5000 {
+            tree.insert(key, key).unwrap();
+        }
+        // Evicting dirty pages has written some of these changes to the file
+        drop(tree);
+
+        let mut tree = DiskBPlusTree::open(&path.0).unwrap();
+        assert_eq!(tree.len(), 1000);
+        assert_eq!(
+            collect(&mut tree),
+            (0..1000).map(|key| (key, key)).collect::<Vec<_>>()
+        );
+        assert!(!tree.free.is_empty());
+    }
+
+    #[test]
+    fn survives_torn_meta_page() {
+        let path = TempPath::new("torn");
+        let mut tree = DiskBPlusTree::open(&path.0).unwrap();
+        for key in 0..500 {
+            tree.insert(key, 1).unwrap();
+        }
+        tree.commit().unwrap();
+        for key in 0..500 {
+            tree.insert(key, 2).unwrap();
+        }
+        tree.commit().unwrap();
+        let slot = tree.sequence % META_PAGES;
+        drop(tree);
+
+        // Only the first half of the last meta page reached the disk
+        let mut file = OpenOptions::new().write(true).open(&path.0).unwrap();
+        file.seek(SeekFrom::Start(slot * PAGE_SIZE as u64 + 16))
+            .unwrap();
+        file.write_all(&[0xff; 16]).unwrap();
+        drop(file);
+
+        let mut tree = DiskBPlusTree::open(&path.0).unwrap();
+        assert_eq!(tree.len(), 500);
+        assert!(collect(&mut tree).iter().all(|&(_, value)| value == 1));
+    }
+
+    #[test]
+    fn ignores_partial_page() {
+        let path = TempPath::new("partial");
+        let mut tree = DiskBPlusTree::open(&path.0).unwrap();
+        tree.insert(7, 49).unwrap();
+        tree.commit().unwrap();
+        drop(tree);
+
+        let mut file = OpenOptions::new().append(true).open(&path.0).unwrap();
+        file.write_all(&[0xab; 100]).unwrap();
+        drop(file);
+
+        let mut tree = DiskBPlusTree::open(&path.0).unwrap();
+        tree.insert(8, 64).unwrap();
+        tree.commit().unwrap();
+        drop(tree);
+        let mut tree = DiskBPlusTree::open(&path.0).unwrap();
+        assert_eq!(collect(&mut tree), vec![(7, 49), (8, 64)]);
+    }
+
+    #[test]
+    fn rejects_file_without_meta_page() {
+        let path = TempPath::new("garbage");
+        fs::write(&path.0, vec![0x5a; 3 * PAGE_SIZE]).unwrap();
+        let error = DiskBPlusTree::open(&path.0).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn reuses_released_pages() {
+        let path = TempPath::new("reuse");
+        let mut tree = DiskBPlusTree::open(&path.0).unwrap();
+        for round in 0..50 {
+            for key in 0..2000 {
+                tree.insert(key, round).unwrap();
+            }
+            tree.commit().unwrap();
+        }
+        // The tree has 16 leaves and a root, and each round copies all of them
+        assert!(file_pages(&path) < 40);
+
+        for key in 0..2000 {
+            tree.remove(key).unwrap();
+        }
+        tree.commit().unwrap();
+        assert_eq!(tree.root, NO_PAGE);
+        assert!(file_pages(&path) < 40);
+    }
+
+    #[test]
+    fn removal_merges_underfull_nodes() {
+        let path = TempPath::new("merge");
+        let mut tree = DiskBPlusTree::with_pool_size(&path.0, 16).unwrap();
+        for key in 0..20_000 {
+            tree.insert(key, key).unwrap();
+        }
+        // Leaves split in half when filled in order, so about 157 of them
+        assert!(check(&mut tree) > 150);
+
+        for key in (0..20_000).filter(|key| key % 10 != 0) {
+            assert_eq!(tree.remove(key).unwrap(), Some(key));
+        }
+        assert_eq!(tree.len(), 2000);
+        // 2000 pairs fit in 16 leaves at least half full, below a root
+        assert!(check(&mut tree) <= 17);
+        let expected: Vec<_> = (0..20_000).step_by(10).map(|key| (key, key)).collect();
+        assert_eq!(collect(&mut tree), expected);
+
+        for key in (0..2000).rev().map(|i| i * 10) {
+            tree.remove(key).unwrap();
+            if key % 500 == 0 {
+                check(&mut tree);
+            }
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.root, NO_PAGE);
+    }
+
+    #[test]
+    fn range_bounds() {
+        let path = TempPath::new("range");
+        let mut tree = DiskBPlusTree::open(&path.0).unwrap();
+        for key in (0..3000).map(|key| key * 2) {
+            tree.insert(key, key / 2).unwrap();
+        }
+        let keys = |range: DiskRange| -> Vec<u64> { range.map(|entry| entry.unwrap().0).collect() };
+
+        assert_eq!(keys(tree.range(9..15)), vec![10, 12, 14]);
+        assert_eq!(keys(tree.range(10..=14)), vec![10, 12, 14]);
+        assert_eq!(
+            keys(tree.range((Bound::Excluded(10), Bound::Included(16)))),
+            vec![12, 14, 16]
+        );
+        assert_eq!(keys(tree.range(5990..)), vec![5990, 5992, 5994, 5996, 5998]);
+        assert_eq!(keys(tree.range(..4)), vec![0, 2]);
+        assert_eq!(keys(tree.range(6000..)), Vec::<u64>::new());
+        assert_eq!(tree.range(1000..2000).count(), 500);
+        assert_eq!(tree.iter().count(), 3000);
+    }
+
+    #[test]
+    fn random_operations() {
+        for seed in 0..4 {
+            let path = TempPath::new(&format!("random-{}", seed));
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut tree = DiskBPlusTree::with_pool_size(&path.0, 8).unwrap();
+            let mut committed = BTreeMap::new();
+            let mut expected = BTreeMap::new();
+
+            for step in 0..30_000 {
+                let key = rng.gen_range(0, 20_000);
+                match rng.gen_range(0, 10) {
+                    0..=5 => {
+                        let value = rng.gen();
+                        assert_eq!(
+                            tree.insert(key, value).unwrap(),
+                            expected.insert(key, value)
+                        );
+                    }
+                    6..=8 => assert_eq!(tree.remove(key).unwrap(), expected.remove(&key)),
+                    _ => assert_eq!(tree.get(key).unwrap(), expected.get(&key).copied()),
+                }
+                assert_eq!(tree.len(), expected.len());
+
+                if step % 500 == 0 {
+                    check(&mut tree);
+                    tree.commit().unwrap();
+                    committed = expected.clone();
+                }
+                if step % 4000 == 0 {
+                    // Reopening without a commit goes back to the last one
+                    drop(tree);
+                    tree = DiskBPlusTree::with_pool_size(&path.0, 8).unwrap();
+                    expected = committed.clone();
+                    assert_eq!(tree.len(), expected.len());
+                }
+            }
+
+            let all: Vec<_> = expected.iter().map(|(&key, &value)| (key, value)).collect();
+            assert_eq!(collect(&mut tree), all);
+            for _ in 0..50 {
+                let start = rng.gen_range(0, 20_000);
+                let end = rng.gen_range(start, 20_001);
+                let range: Vec<_> = tree.range(start..end).map(Result::unwrap).collect();
+                let expected_range: Vec<_> = expected
+                    .range(start..end)
+                    .map(|(&key, &value)| (key, value))
+                    .collect();
+                assert_eq!(range, expected_range);
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Size of every page in the file, in bytes
+pub const PAGE_SIZE: usize = 4096;
+
+pub(super) type PageId = u64;
+
+pub(super) type Page = [u8; PAGE_SIZE];
+
+struct Frame {
+    data: Box<Page>,
+    dirty: bool,
+    // Value of the pool's clock when the page was last used
+    last_used: u64,
+}
+
+/// A page file with a fixed number of pages cached in memory
+///
+/// Pages are read and written through the cache. When it is full, the least
+/// recently used page is evicted, and written to the file first if it is
+/// dirty. Nothing is synced to disk until `flush` is called.
+pub(super) struct Pager {
+    file: File,
+    capacity: usize,
+    frames: HashMap<PageId, Frame>,
+    clock: u64,
+}
+
+impl Pager {
+    pub(super) fn new(file: File, capacity: usize) -> Self {
+        assert!(capacity > 0, "Buffer pool must hold at least one page");
+        Self {
+            file,
+            capacity,
+            frames: HashMap::with_capacity(capacity),
+            clock: 0,
+        }
+    }
+
+    /// Returns the number of whole pages in the file
+    pub(super) fn file_pages(&self) -> io::Result<u64> {
+        Ok(self.file.metadata()?.len() / PAGE_SIZE as u64)
+    }
+
+    pub(super) fn read(&mut self, id: PageId) -> io::Result<&Page> {
+        self.clock += 1;
+        if !self.frames.contains_key(&id) {
+            self.make_room()?;
+            let mut data = Box::new([0; PAGE_SIZE]);
+            self.read_direct(id, &mut data)?;
+            self.frames.insert(
+                id,
+                Frame {
+                    data,
+                    dirty: false,
+                    last_used: 0,
+                },
+            );
+        }
+        let frame = self.frames.get_mut(&id).unwrap();
+        frame.last_used = self.clock;
+        Ok(&frame.data)
+    }
+
+    pub(super) fn write(&mut self, id: PageId, data: &Page) -> io::Result<()> {
+        self.clock += 1;
+        if !self.frames.contains_key(&id) {
+            self.make_room()?;
+        }
+        let clock = self.clock;
+        let frame = self.frames.entry(id).or_insert_with(|| Frame {
+            data: Box::new([0; PAGE_SIZE]),
+            dirty: true,
+            last_used: clock,
+        });
+        frame.data.copy_from_slice(data);
+        frame.dirty = true;
+        frame.last_used = clock;
+        Ok(())
+    }
+
+    /// Writes all dirty pages to the file and waits until they are on disk
+    pub(super) fn flush(&mut self) -> io::Result<()> {
+        let mut dirty: Vec<_> = self
+            .frames
+            .iter()
+            .filter(|(_, frame)| frame.dirty)
+            .map(|(id, _)| *id)
+            .collect();
+        dirty.sort_unstable();
+        for id in dirty {
+            let frame = self.frames.get_mut(&id).unwrap();
+            Self::write_at(&mut self.file, id, &frame.data)?;
+            frame.dirty = false;
+        }
+        self.file.sync_data()
+    }
+
+    /// Reads a page from the file, bypassing the cache
+    ///
+    /// Pages past the end of the file read as zeroes.
+    pub(super) fn read_direct(&mut self, id: PageId, data: &mut Page) -> io::Result<()> {
+        if id >= self.file_pages()? {
+            data.fill(0);
+            return Ok(());
+        }
+        self.file.seek(SeekFrom::Start(id * PAGE_SIZE as u64))?;
+        self.file.read_exact(data)
+    }
+
+    /// Writes a page to the file and syncs it, bypassing the cache
+    pub(super) fn write_direct(&mut self, id: PageId, data: &Page) -> io::Result<()> {
+        Self::write_at(&mut self.file, id, data)?;
+        self.file.sync_data()
+    }
+
+    fn write_at(file: &mut File, id: PageId, data: &Page) -> io::Result<()> {
+        file.seek(SeekFrom::Start(id * PAGE_SIZE as u64))?;
+        file.write_all(data)
+    }
+
+    /// Evicts the least recently used page if the cache is full
+    fn make_room(&mut self) -> io::Result<()> {
+        if self.frames.len() < self.capacity {
+            return Ok(());
+        }
+        let victim = self
+            .frames
+            .iter()
+            .min_by_key(|(_, frame)| frame.last_used)
+            .map(|(id, _)| *id)
+            .unwrap();
+        let frame = self.frames.remove(&victim).unwrap();
+        if frame.dirty {
+            Self::write_at(&mut self.file, victim, &frame.data)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs::{self, OpenOptions};
+
+    #[test]
+    fn evicted_pages_are_written_back() {
+        let path = env::temp_dir().join(format!("pager-{}.db", std::process::id()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+        let mut pager = Pager::new(file, 2);
+        for id in 0..5 {
+            pager.write(id, &[id as u8; PAGE_SIZE]).unwrap();
+        }
+        assert_eq!(pager.frames.len(), 2);
+        for id in 0..5 {
+            assert_eq!(pager.read(id).unwrap()[100], id as u8);
+        }
+        pager.flush().unwrap();
+        assert_eq!(pager.file_pages().unwrap(), 5);
+        fs::remove_file(&path).unwrap();
+    }
+}
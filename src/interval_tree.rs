@@ -0,0 +1,359 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::ops::{Bound, Range};
+
+use crate::avl_tree::AvlTree;
+use crate::binary_search_tree::Augment;
+
+/// Orders intervals by start, then by end
+struct IntervalKey<K>(Range<K>);
+
+impl<K: Ord> Ord for IntervalKey<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.0.start, &self.0.end).cmp(&(&other.0.start, &other.0.end))
+    }
+}
+
+impl<K: Ord> PartialOrd for IntervalKey<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord> PartialEq for IntervalKey<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord> Eq for IntervalKey<K> {}
+
+/// An interval and its value, ordered by the interval alone
+struct Entry<K, V> {
+    interval: IntervalKey<K>,
+    value: V,
+}
+
+impl<K: Ord, V> Ord for Entry<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.interval.cmp(&other.interval)
+    }
+}
+
+impl<K: Ord, V> PartialOrd for Entry<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> PartialEq for Entry<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord, V> Eq for Entry<K, V> {}
+
+impl<K, V> Borrow<IntervalKey<K>> for Entry<K, V> {
+    fn borrow(&self) -> &IntervalKey<K> {
+        &self.interval
+    }
+}
+
+/// Keeps the largest end point of each subtree
+struct MaxEnd;
+
+impl<K: Ord + Clone, V> Augment<Entry<K, V>, ()> for MaxEnd {
+    type Summary = Option<K>;
+
+    fn identity() -> Option<K> {
+        None
+    }
+
+    fn summarize(entry: &Entry<K, V>, _value: &()) -> Option<K> {
+        Some(entry.interval.0.end.clone())
+    }
+
+    fn combine(left: &Option<K>, right: &Option<K>) -> Option<K> {
+        left.as_ref().max(right.as_ref()).cloned()
+    }
+}
+
+type Tree<K, V> = AvlTree<Entry<K, V>, MaxEnd>;
+
+// An entry whose left subtree has been visited, along with its right subtree
+type Pending<'a, K, V> = (&'a Entry<K, V>, &'a Tree<K, V>);
+
+/// A map from half-open intervals `[start, end)` to values, supporting overlap queries
+///
+/// Intervals are stored in an `AvlTree` ordered by their start points, where
+/// every subtree is augmented with the largest end point inside it. A query
+/// skips every subtree whose largest end point lies before the queried
+/// interval, and stops as soon as the start points pass its end.
+///
+/// The tree rebalances itself, so its height is O(log n) whatever the order
+/// of insertion, and insertion and removal take O(log n) time. Apart from
+/// O(log n) nodes on the path to the end of the query, every node a query
+/// visits is an ancestor of a reported interval. A query reporting k
+/// intervals therefore takes O(log n + k log n) time, and O(log n) when
+/// nothing overlaps. Specialized structures such as priority search trees
+/// reduce this to O(log n + k).
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::interval_tree::IntervalTree;
+///
+/// let mut meetings = IntervalTree::new();
+/// meetings.insert(9..10, "standup");
+/// meetings.insert(13..15, "planning");
+/// meetings.insert(14..16, "review");
+///
+/// let busy: Vec<_> = meetings.overlapping(14..15).map(|(_, name)| *name).collect();
+/// assert_eq!(busy, vec!["planning", "review"]);
+///
+/// let at_nine: Vec<_> = meetings.containing(9).map(|(_, name)| *name).collect();
+/// assert_eq!(at_nine, vec!["standup"]);
+/// ```
+pub struct IntervalTree<K: Ord + Clone, V> {
+    tree: Tree<K, V>,
+}
+
+impl<K: Ord + Clone, V> Default for IntervalTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V> IntervalTree<K, V> {
+    pub fn new() -> Self {
+        Self {
+            tree: Tree::default(),
+        }
+    }
+
+    /// Inserts an interval, returning the value previously stored for the same interval
+    pub fn insert(&mut self, interval: Range<K>, value: V) -> Option<V> {
+        let entry = Entry {
+            interval: IntervalKey(interval),
+            value,
+        };
+        self.tree.replace(entry).map(|entry| entry.value)
+    }
+
+    /// Returns the value stored for exactly `interval`
+    pub fn get(&self, interval: &Range<K>) -> Option<&V> {
+        self.tree
+            .get(&IntervalKey(interval.clone()))
+            .map(|entry| &entry.value)
+    }
+
+    /// Removes an interval, returning the value that was stored for it
+    pub fn remove(&mut self, interval: &Range<K>) -> Option<V> {
+        self.tree
+            .remove(&IntervalKey(interval.clone()))
+            .map(|entry| entry.value)
+    }
+
+    /// Returns the intervals overlapping `query`, ordered by their start points
+    ///
+    /// Two half-open intervals overlap if they share at least one point, so
+    /// intervals that merely touch the query, such as `[0, 5)` and `[5, 10)`,
+    /// do not overlap.
+    pub fn overlapping(&self, query: Range<K>) -> Overlapping<'_, K, V> {
+        let mut overlapping = Overlapping {
+            stack: Vec::new(),
+            start_bound: Bound::Excluded(query.end.clone()),
+            end_after: query.start.clone(),
+        };
+        if query.start < query.end {
+            overlapping.push_left_spine(&self.tree);
+        }
+        overlapping
+    }
+
+    /// Returns the intervals containing `point`, ordered by their start points
+    pub fn containing(&self, point: K) -> Overlapping<'_, K, V> {
+        let mut overlapping = Overlapping {
+            stack: Vec::new(),
+            start_bound: Bound::Included(point.clone()),
+            end_after: point,
+        };
+        overlapping.push_left_spine(&self.tree);
+        overlapping
+    }
+}
+
+/// An iterator over the intervals of an `IntervalTree` that match a query
+///
+/// Matching intervals start before `start_bound` and end after `end_after`.
+pub struct Overlapping<'a, K: Ord + Clone, V> {
+    stack: Vec<Pending<'a, K, V>>,
+    start_bound: Bound<K>,
+    end_after: K,
+}
+
+impl<'a, K: Ord + Clone, V> Overlapping<'a, K, V> {
+    fn starts_in_bound(&self, start: &K) -> bool {
+        match self.start_bound {
+            Bound::Included(ref bound) => start <= bound,
+            Bound::Excluded(ref bound) => start < bound,
+            Bound::Unbounded => true,
+        }
+    }
+
+    fn push_left_spine(&mut self, mut tree: &'a Tree<K, V>) {
+        while let Some((left, entry, right)) = tree.split_root() {
+            // Every interval in this subtree ends before the query
+            if tree
+                .root_summary()
+                .unwrap()
+                .as_ref()
+                .map_or(true, |max_end| *max_end <= self.end_after)
+            {
+                break;
+            }
+            self.stack.push((entry, right));
+            tree = left;
+        }
+    }
+}
+
+impl<'a, K: Ord + Clone, V> Iterator for Overlapping<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((entry, right)) = self.stack.pop() {
+            let interval = &entry.interval.0;
+            if !self.starts_in_bound(&interval.start) {
+                // Intervals are visited by increasing start, so none of the remaining ones match
+                self.stack.clear();
+                return None;
+            }
+            self.push_left_spine(right);
+            if interval.end > self.end_after {
+                return Some((interval, &entry.value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect<'a>(iter: impl Iterator<Item = (&'a Range<i32>, &'a i32)>) -> Vec<Range<i32>> {
+        iter.map(|(interval, _)| interval.clone()).collect()
+    }
+
+    #[test]
+    fn empty_tree_has_no_overlaps() {
+        let tree: IntervalTree<i32, ()> = IntervalTree::new();
+        assert_eq!(tree.overlapping(0..10).count(), 0);
+        assert_eq!(tree.containing(0).count(), 0);
+    }
+
+    #[test]
+    fn insert_get_and_remove() {
+        let mut tree = IntervalTree::new();
+        assert_eq!(tree.insert(1..5, 'a'), None);
+        assert_eq!(tree.insert(1..3, 'b'), None);
+        assert_eq!(tree.insert(1..5, 'c'), Some('a'));
+
+        assert_eq!(tree.get(&(1..5)), Some(&'c'));
+        assert_eq!(tree.get(&(1..4)), None);
+        assert_eq!(tree.remove(&(1..5)), Some('c'));
+        assert_eq!(tree.remove(&(1..5)), None);
+        assert_eq!(tree.get(&(1..3)), Some(&'b'));
+    }
+
+    #[test]
+    fn touching_intervals_do_not_overlap() {
+        let mut tree = IntervalTree::new();
+        tree.insert(0..5, 0);
+        tree.insert(10..15, 0);
+
+        assert_eq!(collect(tree.overlapping(5..10)), vec![]);
+        assert_eq!(collect(tree.overlapping(4..11)), vec![0..5, 10..15]);
+        assert_eq!(collect(tree.containing(5)), vec![]);
+        assert_eq!(collect(tree.containing(10)), vec![10..15]);
+    }
+
+    #[test]
+    fn empty_query_overlaps_nothing() {
+        let mut tree = IntervalTree::new();
+        tree.insert(0..10, 0);
+
+        assert_eq!(collect(tree.overlapping(5..5)), vec![]);
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        let mut tree = IntervalTree::new();
+        let mut intervals = Vec::new();
+        for i in 0..200 {
+            let start = (i * 37) % 101;
+            let end = start + 1 + (i * 13) % 17;
+            tree.insert(start..end, i);
+            intervals.push(start..end);
+        }
+        intervals.sort_by_key(|interval| (interval.start, interval.end));
+        intervals.dedup();
+
+        for start in 0..120 {
+            for length in &[1, 5, 30] {
+                let query = start..start + length;
+                let expected: Vec<_> = intervals
+                    .iter()
+                    .filter(|interval| interval.start < query.end && query.start < interval.end)
+                    .cloned()
+                    .collect();
+                assert_eq!(collect(tree.overlapping(query)), expected);
+            }
+
+            let expected: Vec<_> = intervals
+                .iter()
+                .filter(|interval| interval.contains(&start))
+                .cloned()
+                .collect();
+            assert_eq!(collect(tree.containing(start)), expected);
+        }
+    }
+
+    #[test]
+    fn queries_after_removal() {
+        let mut tree = IntervalTree::new();
+        tree.insert(5..20, 0);
+        tree.insert(1..3, 1);
+        tree.insert(8..9, 2);
+        tree.insert(10..12, 3);
+        tree.insert(2..30, 4);
+
+        // The long interval is the only reason to visit the left subtree
+        assert_eq!(collect(tree.containing(25)), vec![2..30]);
+        tree.remove(&(2..30));
+        assert_eq!(collect(tree.containing(25)), vec![]);
+
+        tree.remove(&(5..20));
+        assert_eq!(collect(tree.overlapping(0..100)), vec![1..3, 8..9, 10..12]);
+        assert_eq!(collect(tree.containing(11)), vec![10..12]);
+    }
+
+    #[test]
+    fn sorted_intervals_keep_the_tree_balanced() {
+        let n = 3000;
+        let mut tree = IntervalTree::new();
+        for start in 0..n {
+            tree.insert(start..start + 10, start);
+        }
+        // The height of an AVL tree with n nodes is below 1.4405 log2(n + 2) - 0.3277
+        assert!(tree.tree.height() <= 16);
+
+        let expected: Vec<_> = (1491..=1500).map(|start| start..start + 10).collect();
+        assert_eq!(collect(tree.containing(1500)), expected);
+        assert_eq!(tree.remove(&(n - 1..n + 9)), Some(n - 1));
+        let expected: Vec<_> = (2986..n - 1).map(|start| start..start + 10).collect();
+        assert_eq!(collect(tree.overlapping(2995..3100)), expected);
+    }
+}
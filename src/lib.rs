@@ -1,6 +1,12 @@
 pub mod aho_corasick;
+pub mod avl_tree;
 pub mod binary_search_tree;
+pub mod btree;
 pub mod heap_sort;
 pub mod hyper_log_log;
+pub mod interval_tree;
 pub mod merge_sort;
 pub mod quick_sort;
+pub mod red_black_tree;
+pub mod splay_tree;
+pub mod treap;
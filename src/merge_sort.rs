@@ -1,3 +1,6 @@
+use std::cmp::Ordering;
+use std::mem;
+
 fn merge<T: PartialOrd + Copy>(left_vector: &[T], right_vector: &[T], result: &mut [T]) {
     let mut left_id = 0;
     let mut right_id = 0;
@@ -35,6 +38,36 @@ pub fn merge_sort<T: PartialOrd + Copy>(vector: &mut [T]) {
     vector.copy_from_slice(&intermediary_vector);
 }
 
+/// Sorts by a comparison function, keeping equal elements in their original order
+///
+/// Elements are moved rather than copied, so they need not be `Copy`.
+pub fn merge_sort_by<T, F: FnMut(&T, &T) -> Ordering>(vector: &mut Vec<T>, mut compare: F) {
+    let elements = mem::take(vector);
+    *vector = sort_owned(elements, &mut compare);
+}
+
+fn sort_owned<T, F: FnMut(&T, &T) -> Ordering>(mut left: Vec<T>, compare: &mut F) -> Vec<T> {
+    if left.len() < 2 {
+        return left;
+    }
+    let right = left.split_off(left.len() / 2);
+    let mut left = sort_owned(left, compare).into_iter().peekable();
+    let mut right = sort_owned(right, compare).into_iter().peekable();
+
+    let mut result = Vec::with_capacity(left.len() + right.len());
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        // Ties go to the left half to keep the sort stable
+        if compare(r, l) == Ordering::Less {
+            result.extend(right.next());
+        } else {
+            result.extend(left.next());
+        }
+    }
+    result.extend(left);
+    result.extend(right);
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,4 +106,24 @@ mod tests {
         merge_sort(&mut v);
         assert_eq!(v, vec!['a', 'a', 'b', 'b']);
     }
+
+    #[test]
+    fn sort_by_comparator() {
+        let mut v = vec![3, 1, 4, 1, 5, 9, 2, 6];
+        merge_sort_by(&mut v, |a, b| b.cmp(a));
+        assert_eq!(v, vec![9, 6, 5, 4, 3, 2, 1, 1]);
+    }
+
+    #[test]
+    fn sort_by_is_stable_and_moves_elements() {
+        let mut v: Vec<(i32, String)> = vec![
+            (2, "a".to_string()),
+            (1, "b".to_string()),
+            (2, "c".to_string()),
+            (1, "d".to_string()),
+        ];
+        merge_sort_by(&mut v, |a, b| a.0.cmp(&b.0));
+        let order: Vec<&str> = v.iter().map(|(_, s)| s.as_str()).collect();
+        assert_eq!(order, vec!["b", "d", "a", "c"]);
+    }
 }
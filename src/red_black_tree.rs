@@ -0,0 +1,877 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::mem;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Color {
+    Red,
+    Black,
+}
+
+struct Node<T: Ord> {
+    item: T,
+    color: Color,
+    left: RedBlackTree<T>,
+    right: RedBlackTree<T>,
+}
+
+impl<T: Ord> Node<T> {
+    fn new(item: T) -> Self {
+        Node {
+            item,
+            color: Color::Red,
+            left: RedBlackTree::new(),
+            right: RedBlackTree::new(),
+        }
+    }
+}
+
+/// A red-black tree
+///
+/// Offers the same operations as `BinarySearchTree`, but keeps itself balanced
+/// by coloring every node red or black such that no red node has a red child,
+/// and every path from the root to an empty subtree passes the same number of
+/// black nodes. This bounds the height by 2 log2(n + 1).
+///
+/// Compared to `AvlTree` the balance is looser, which means fewer rotations:
+/// at most two per insertion and at most three per deletion.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::red_black_tree::RedBlackTree;
+///
+/// let mut tree = RedBlackTree::new();
+/// for i in 0..100 {
+///     tree.insert(i);
+/// }
+/// tree.delete(50);
+///
+/// assert!(tree.contains(&49));
+/// assert!(!tree.contains(&50));
+/// assert_eq!(tree.iter().count(), 99);
+/// ```
+pub struct RedBlackTree<T: Ord> {
+    node: Option<Box<Node<T>>>,
+}
+
+impl<T: Ord> Default for RedBlackTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Drop for RedBlackTree<T> {
+    fn drop(&mut self) {
+        // Frees the nodes iteratively, like the trees whose paths can be long
+        let mut nodes: Vec<_> = self.node.take().into_iter().collect();
+        while let Some(mut node) = nodes.pop() {
+            nodes.extend(node.left.node.take());
+            nodes.extend(node.right.node.take());
+        }
+    }
+}
+
+impl<T: Ord> RedBlackTree<T> {
+    pub fn new() -> Self {
+        Self { node: None }
+    }
+
+    fn is_red(&self) -> bool {
+        self.node
+            .as_ref()
+            .map_or(false, |node| node.color == Color::Red)
+    }
+
+    fn set_color(&mut self, color: Color) {
+        if let Some(ref mut node) = self.node {
+            node.color = color;
+        }
+    }
+
+    fn rotate_right(&mut self) {
+        let mut node = self.node.take().unwrap();
+        let mut left = node.left.node.take().unwrap();
+        node.left.node = left.right.node.take();
+        left.right.node = Some(node);
+        self.node = Some(left);
+    }
+
+    fn rotate_left(&mut self) {
+        let mut node = self.node.take().unwrap();
+        let mut right = node.right.node.take().unwrap();
+        node.right.node = right.left.node.take();
+        right.left.node = Some(node);
+        self.node = Some(right);
+    }
+
+    pub fn insert(&mut self, item: T) {
+        self.insert_below(item);
+        self.set_color(Color::Black);
+    }
+
+    fn insert_below(&mut self, item: T) {
+        let node = match self.node {
+            Some(ref mut node) => node,
+            None => {
+                self.node = Some(Box::new(Node::new(item)));
+                return;
+            }
+        };
+        match item.cmp(&node.item) {
+            Ordering::Greater => node.right.insert_below(item),
+            Ordering::Less => node.left.insert_below(item),
+            Ordering::Equal => return,
+        }
+        self.fix_red_red();
+    }
+
+    /// Resolves a red child with a red child of its own below this node
+    ///
+    /// Either pushes the redness up by recoloring, or restructures the
+    /// subtree with one or two rotations.
+    fn fix_red_red(&mut self) {
+        let node = self.node.as_mut().unwrap();
+        let left_violation =
+            node.left.is_red() && (node.left.left().is_red() || node.left.right().is_red());
+        let right_violation =
+            node.right.is_red() && (node.right.left().is_red() || node.right.right().is_red());
+        if !left_violation && !right_violation {
+            return;
+        }
+
+        if node.left.is_red() && node.right.is_red() {
+            node.color = Color::Red;
+            node.left.set_color(Color::Black);
+            node.right.set_color(Color::Black);
+        } else if left_violation {
+            if node.left.right().is_red() {
+                node.left.rotate_left();
+            }
+            self.rotate_right();
+            self.set_color(Color::Black);
+            self.right_mut().set_color(Color::Red);
+        } else {
+            if node.right.left().is_red() {
+                node.right.rotate_right();
+            }
+            self.rotate_left();
+            self.set_color(Color::Black);
+            self.left_mut().set_color(Color::Red);
+        }
+    }
+
+    pub fn delete(&mut self, item: T) {
+        self.remove(&item);
+    }
+
+    /// Removes the item equal to `item` and returns it
+    pub fn remove<Q>(&mut self, item: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (removed, _) = self.remove_below(item);
+        self.set_color(Color::Black);
+        removed
+    }
+
+    /// Removes `item` from this subtree and returns it, along with whether the black-height shrank
+    fn remove_below<Q>(&mut self, item: &Q) -> (Option<T>, bool)
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = match self.node {
+            Some(ref mut node) => node,
+            None => return (None, false),
+        };
+        match item.cmp(node.item.borrow()) {
+            Ordering::Greater => {
+                let (removed, short) = node.right.remove_below(item);
+                (removed, short && self.fix_short_right())
+            }
+            Ordering::Less => {
+                let (removed, short) = node.left.remove_below(item);
+                (removed, short && self.fix_short_left())
+            }
+            Ordering::Equal => {
+                if node.left.node.is_some() && node.right.node.is_some() {
+                    let (min, short) = node.right.find_and_delete_min();
+                    let removed = mem::replace(&mut node.item, min);
+                    (Some(removed), short && self.fix_short_right())
+                } else {
+                    let (removed, short) = self.delete_node();
+                    (Some(removed), short)
+                }
+            }
+        }
+    }
+
+    fn find_and_delete_min(&mut self) -> (T, bool) {
+        let node = self.node.as_mut().unwrap();
+        if node.left.node.is_some() {
+            let (min, short) = node.left.find_and_delete_min();
+            (min, short && self.fix_short_left())
+        } else {
+            let mut node = self.node.take().unwrap();
+            self.node = node.right.node.take();
+            let short = node.color == Color::Black && !self.is_red();
+            self.set_color(Color::Black);
+            (node.item, short)
+        }
+    }
+
+    /// Removes the root of this subtree, which has at most one child
+    ///
+    /// In a valid red-black tree such a child is a red leaf, so the
+    /// black-height only shrinks when a black leaf is removed.
+    fn delete_node(&mut self) -> (T, bool) {
+        let mut node = self.node.take().unwrap();
+        self.node = node.left.node.take().or_else(|| node.right.node.take());
+        let short = node.color == Color::Black && !self.is_red();
+        self.set_color(Color::Black);
+        (node.item, short)
+    }
+
+    /// Restores the black-height after the left subtree lost a black node
+    ///
+    /// Returns whether the black-height of this whole subtree shrank.
+    fn fix_short_left(&mut self) -> bool {
+        if self.right().is_red() {
+            self.rotate_left();
+            self.set_color(Color::Black);
+            self.left_mut().set_color(Color::Red);
+            // The short subtree now has a red parent, which always absorbs the deficit
+            self.left_mut().fix_short_left();
+            return false;
+        }
+
+        let node = self.node.as_mut().unwrap();
+        if !node.right.left().is_red() && !node.right.right().is_red() {
+            node.right.set_color(Color::Red);
+            let short = node.color == Color::Black;
+            node.color = Color::Black;
+            return short;
+        }
+
+        if !node.right.right().is_red() {
+            node.right.rotate_right();
+            node.right.set_color(Color::Black);
+            node.right.right_mut().set_color(Color::Red);
+        }
+        let color = node.color;
+        self.rotate_left();
+        self.set_color(color);
+        self.left_mut().set_color(Color::Black);
+        self.right_mut().set_color(Color::Black);
+        false
+    }
+
+    /// Restores the black-height after the right subtree lost a black node
+    ///
+    /// Returns whether the black-height of this whole subtree shrank.
+    fn fix_short_right(&mut self) -> bool {
+        if self.left().is_red() {
+            self.rotate_right();
+            self.set_color(Color::Black);
+            self.right_mut().set_color(Color::Red);
+            // The short subtree now has a red parent, which always absorbs the deficit
+            self.right_mut().fix_short_right();
+            return false;
+        }
+
+        let node = self.node.as_mut().unwrap();
+        if !node.left.left().is_red() && !node.left.right().is_red() {
+            node.left.set_color(Color::Red);
+            let short = node.color == Color::Black;
+            node.color = Color::Black;
+            return short;
+        }
+
+        if !node.left.left().is_red() {
+            node.left.rotate_left();
+            node.left.set_color(Color::Black);
+            node.left.left_mut().set_color(Color::Red);
+        }
+        let color = node.color;
+        self.rotate_right();
+        self.set_color(color);
+        self.left_mut().set_color(Color::Black);
+        self.right_mut().set_color(Color::Black);
+        false
+    }
+
+    fn left(&self) -> &Self {
+        &self.node.as_ref().unwrap().left
+    }
+
+    fn right(&self) -> &Self {
+        &self.node.as_ref().unwrap().right
+    }
+
+    fn left_mut(&mut self) -> &mut Self {
+        &mut self.node.as_mut().unwrap().left
+    }
+
+    fn right_mut(&mut self) -> &mut Self {
+        &mut self.node.as_mut().unwrap().right
+    }
+
+    /// Returns the stored item equal to `item`
+    pub fn get<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut tree = self;
+        while let Some(ref node) = tree.node {
+            match item.cmp(node.item.borrow()) {
+                Ordering::Greater => tree = &node.right,
+                Ordering::Less => tree = &node.left,
+                Ordering::Equal => return Some(&node.item),
+            }
+        }
+        None
+    }
+
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(item).is_some()
+    }
+
+    /// Returns the number of black nodes on any path from the root to an empty subtree
+    fn black_height(&self) -> usize {
+        let mut height = 0;
+        let mut tree = self;
+        while let Some(ref node) = tree.node {
+            if node.color == Color::Black {
+                height += 1;
+            }
+            tree = &node.left;
+        }
+        height
+    }
+
+    /// Joins two trees and an item, given that `left < item < right`
+    ///
+    /// The item is placed as a red node along the spine of the tree with the
+    /// larger black-height, at the first black subtree whose black-height
+    /// matches the other tree. Red-red violations are then fixed on the way back
+    /// up, as after an insertion.
+    fn join(mut left: Self, item: T, mut right: Self) -> Self {
+        left.set_color(Color::Black);
+        right.set_color(Color::Black);
+        let (left_height, right_height) = (left.black_height(), right.black_height());
+        let mut tree = if left_height > right_height {
+            left.join_right(item, right, left_height, right_height);
+            left
+        } else if right_height > left_height {
+            right.join_left(left, item, right_height, left_height);
+            right
+        } else {
+            let mut node = Node::new(item);
+            node.left = left;
+            node.right = right;
+            Self {
+                node: Some(Box::new(node)),
+            }
+        };
+        tree.set_color(Color::Black);
+        tree
+    }
+
+    /// Places `item` and `right` along the right spine, where `height` is the black-height of this subtree
+    fn join_right(&mut self, item: T, right: Self, height: usize, target: usize) {
+        if !self.is_red() && height == target {
+            let mut node = Node::new(item);
+            node.left = mem::take(self);
+            node.right = right;
+            self.node = Some(Box::new(node));
+            return;
+        }
+        let child_height = if self.is_red() { height } else { height - 1 };
+        self.right_mut()
+            .join_right(item, right, child_height, target);
+        self.fix_red_red();
+    }
+
+    /// Places `left` and `item` along the left spine, where `height` is the black-height of this subtree
+    fn join_left(&mut self, left: Self, item: T, height: usize, target: usize) {
+        if !self.is_red() && height == target {
+            let mut node = Node::new(item);
+            node.left = left;
+            node.right = mem::take(self);
+            self.node = Some(Box::new(node));
+            return;
+        }
+        let child_height = if self.is_red() { height } else { height - 1 };
+        self.left_mut().join_left(left, item, child_height, target);
+        self.fix_red_red();
+    }
+
+    /// Joins two trees, given that every item of `left` is less than every item of `right`
+    fn join_trees(mut left: Self, mut right: Self) -> Self {
+        if right.node.is_none() {
+            left.set_color(Color::Black);
+            return left;
+        }
+        right.set_color(Color::Black);
+        let (min, _) = right.find_and_delete_min();
+        Self::join(left, min, right)
+    }
+
+    /// Splits the tree into the items less than `item`, the item itself and the items greater than it
+    ///
+    /// The two halves may have red roots.
+    fn split(mut self, item: &T) -> (Self, Option<T>, Self) {
+        let node = match self.node.take() {
+            Some(node) => *node,
+            None => return (Self::new(), None, Self::new()),
+        };
+        match item.cmp(&node.item) {
+            Ordering::Less => {
+                let (less, found, greater) = node.left.split(item);
+                (less, found, Self::join(greater, node.item, node.right))
+            }
+            Ordering::Greater => {
+                let (less, found, greater) = node.right.split(item);
+                (Self::join(node.left, node.item, less), found, greater)
+            }
+            Ordering::Equal => (node.left, Some(node.item), node.right),
+        }
+    }
+
+    /// Returns a tree with the items in `self` or `other`
+    ///
+    /// Like the other bulk set operations, this splits `self` around the root
+    /// of `other`, recurses on both halves and joins the results, without
+    /// inserting items one by one. Since black-heights are not stored but
+    /// recomputed by every join, combining trees of sizes m <= n takes
+    /// O(m log n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithms::red_black_tree::RedBlackTree;
+    ///
+    /// let mut evens = RedBlackTree::new();
+    /// let mut small = RedBlackTree::new();
+    /// for i in 0..10 {
+    ///     evens.insert(2 * i);
+    ///     small.insert(i);
+    /// }
+    ///
+    /// let union = evens.union(small);
+    /// assert_eq!(union.iter().count(), 15);
+    /// assert!(union.contains(&7));
+    /// assert!(union.contains(&18));
+    /// ```
+    pub fn union(self, mut other: Self) -> Self {
+        let node = match other.node.take() {
+            Some(node) => *node,
+            None => return self,
+        };
+        if self.node.is_none() {
+            return Self::join(node.left, node.item, node.right);
+        }
+        let (less, _, greater) = self.split(&node.item);
+        let left = less.union(node.left);
+        let right = greater.union(node.right);
+        Self::join(left, node.item, right)
+    }
+
+    /// Returns a tree with the items in both `self` and `other`
+    pub fn intersection(self, mut other: Self) -> Self {
+        let node = match other.node.take() {
+            Some(node) if self.node.is_some() => *node,
+            _ => return Self::new(),
+        };
+        let (less, found, greater) = self.split(&node.item);
+        let left = less.intersection(node.left);
+        let right = greater.intersection(node.right);
+        match found {
+            Some(item) => Self::join(left, item, right),
+            None => Self::join_trees(left, right),
+        }
+    }
+
+    /// Returns a tree with the items in `self` but not in `other`
+    pub fn difference(mut self, mut other: Self) -> Self {
+        let node = match other.node.take() {
+            Some(node) if self.node.is_some() => *node,
+            _ => {
+                self.set_color(Color::Black);
+                return self;
+            }
+        };
+        let (less, _, greater) = self.split(&node.item);
+        let left = less.difference(node.left);
+        let right = greater.difference(node.right);
+        Self::join_trees(left, right)
+    }
+
+    /// Returns a tree with the items in exactly one of `self` and `other`
+    pub fn symmetric_difference(mut self, mut other: Self) -> Self {
+        let node = match other.node.take() {
+            Some(node) => *node,
+            None => {
+                self.set_color(Color::Black);
+                return self;
+            }
+        };
+        if self.node.is_none() {
+            return Self::join(node.left, node.item, node.right);
+        }
+        let (less, found, greater) = self.split(&node.item);
+        let left = less.symmetric_difference(node.left);
+        let right = greater.symmetric_difference(node.right);
+        match found {
+            Some(_) => Self::join_trees(left, right),
+            None => Self::join(left, node.item, right),
+        }
+    }
+
+    /// Returns an iterator over the items in sorted order
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left_spine(self);
+        iter
+    }
+}
+
+/// An in-order iterator over the items of a `RedBlackTree`
+pub struct Iter<'a, T: Ord> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T: Ord> Iter<'a, T> {
+    fn push_left_spine(&mut self, mut tree: &'a RedBlackTree<T>) {
+        while let Some(ref node) = tree.node {
+            self.stack.push(node);
+            tree = &node.left;
+        }
+    }
+}
+
+impl<'a, T: Ord> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        Some(&node.item)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a RedBlackTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks ordering, that no red node has a red child and that every path
+    /// has the same number of black nodes, and returns that black-height
+    fn check_subtree<T: Ord + std::fmt::Debug>(
+        tree: &RedBlackTree<T>,
+        lower: Option<&T>,
+        upper: Option<&T>,
+    ) -> Result<usize, String> {
+        let node = match tree.node {
+            Some(ref node) => node,
+            None => return Ok(1),
+        };
+        if lower.map_or(false, |lower| node.item <= *lower)
+            || upper.map_or(false, |upper| node.item >= *upper)
+        {
+            return Err(format!("{:?} is out of order", node.item));
+        }
+        if node.color == Color::Red && (node.left.is_red() || node.right.is_red()) {
+            return Err(format!("Red node {:?} has a red child", node.item));
+        }
+        let left = check_subtree(&node.left, lower, Some(&node.item))?;
+        let right = check_subtree(&node.right, Some(&node.item), upper)?;
+        if left != right {
+            return Err(format!(
+                "Black-heights {} and {} differ below {:?}",
+                left, right, node.item
+            ));
+        }
+        Ok(left + if node.color == Color::Black { 1 } else { 0 })
+    }
+
+    fn assert_invariants<T: Ord + std::fmt::Debug>(tree: &RedBlackTree<T>) -> usize {
+        assert!(!tree.is_red(), "Root is red");
+        match check_subtree(tree, None, None) {
+            Ok(black_height) => black_height,
+            Err(violation) => panic!("{}", violation),
+        }
+    }
+
+    fn height<T: Ord>(tree: &RedBlackTree<T>) -> usize {
+        tree.node.as_ref().map_or(0, |node| {
+            1 + std::cmp::max(height(&node.left), height(&node.right))
+        })
+    }
+
+    #[test]
+    fn empty_tree_doesnt_contain_element() {
+        let tree: RedBlackTree<i32> = RedBlackTree::new();
+        assert!(!tree.contains(&1));
+        assert_invariants(&tree);
+    }
+
+    #[test]
+    fn multiple_inserted_items_exist() {
+        let mut tree = RedBlackTree::new();
+        for i in &[5, 6, 3, 4, 2, 1] {
+            tree.insert(*i);
+            assert_invariants(&tree);
+        }
+
+        for i in 1..7 {
+            assert!(tree.contains(&i));
+        }
+        assert!(!tree.contains(&7));
+    }
+
+    #[test]
+    fn duplicate_insert_is_ignored() {
+        let mut tree = RedBlackTree::new();
+        tree.insert(1);
+        tree.insert(1);
+        assert_eq!(tree.iter().count(), 1);
+
+        tree.delete(1);
+        assert!(!tree.contains(&1));
+    }
+
+    #[test]
+    fn delete_item_that_does_not_exist() {
+        let mut tree = RedBlackTree::new();
+        tree.delete(1);
+        tree.insert(2);
+        tree.delete(1);
+
+        assert!(tree.contains(&2));
+        assert_invariants(&tree);
+    }
+
+    #[test]
+    fn remove_returns_the_item_and_lookups_take_borrowed_forms() {
+        let mut tree = RedBlackTree::new();
+        for item in &["b", "a", "d", "c"] {
+            tree.insert(item.to_string());
+        }
+
+        assert!(tree.contains("a"));
+        assert!(!tree.contains("e"));
+        assert_eq!(tree.get("c").map(String::as_str), Some("c"));
+
+        assert_eq!(tree.remove("b"), Some(String::from("b")));
+        assert_eq!(tree.remove("b"), None);
+        assert!(!tree.contains("b"));
+        assert_eq!(tree.iter().count(), 3);
+        assert_invariants(&tree);
+    }
+
+    #[test]
+    fn other_items_exist_after_root_with_two_children_deleted() {
+        let mut tree = RedBlackTree::new();
+        for i in &[2, 1, 5, 3, 4] {
+            tree.insert(*i);
+        }
+
+        tree.delete(2);
+
+        assert_invariants(&tree);
+        assert!(!tree.contains(&2));
+        for i in &[1, 3, 4, 5] {
+            assert!(tree.contains(i));
+        }
+    }
+
+    #[test]
+    fn sorted_inserts_keep_invariants() {
+        let n = 10_000;
+        let mut tree = RedBlackTree::new();
+        for i in 0..n {
+            tree.insert(i);
+        }
+
+        assert_invariants(&tree);
+        assert!(height(&tree) as f64 <= 2.0 * ((n + 1) as f64).log2());
+        assert!((0..n).all(|i| tree.contains(&i)));
+    }
+
+    #[test]
+    fn reverse_sorted_inserts_keep_invariants() {
+        let n = 10_000;
+        let mut tree = RedBlackTree::new();
+        for i in (0..n).rev() {
+            tree.insert(i);
+        }
+
+        assert_invariants(&tree);
+        assert!(height(&tree) as f64 <= 2.0 * ((n + 1) as f64).log2());
+    }
+
+    #[test]
+    fn deletes_keep_invariants() {
+        let n = 1000;
+        let mut tree = RedBlackTree::new();
+        for i in 0..n {
+            tree.insert((i * 7919) % n);
+        }
+        for i in 0..n {
+            let item = (i * 104_729) % n;
+            if item % 4 != 0 {
+                tree.delete(item);
+                assert_invariants(&tree);
+            }
+        }
+
+        for i in 0..n {
+            assert_eq!(tree.contains(&i), i % 4 == 0);
+        }
+    }
+
+    #[test]
+    fn delete_everything() {
+        let mut tree = RedBlackTree::new();
+        for i in 0..100 {
+            tree.insert(i);
+        }
+        for i in (0..100).rev() {
+            tree.delete(i);
+            assert_invariants(&tree);
+        }
+        assert!(tree.node.is_none());
+    }
+
+    #[test]
+    fn iter_is_sorted() {
+        let mut tree = RedBlackTree::new();
+        for i in &[5, 1, 9, 3, 7, 2, 8] {
+            tree.insert(*i);
+        }
+        assert_eq!(
+            tree.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 5, 7, 8, 9]
+        );
+    }
+
+    fn tree(items: impl IntoIterator<Item = usize>) -> RedBlackTree<usize> {
+        let mut tree = RedBlackTree::new();
+        for item in items {
+            tree.insert(item);
+        }
+        tree
+    }
+
+    fn sorted(tree: &RedBlackTree<usize>) -> Vec<usize> {
+        tree.iter().copied().collect()
+    }
+
+    #[test]
+    fn join_trees_of_different_black_heights() {
+        for split in &[0, 1, 10, 500, 998, 999] {
+            let left = tree(0..*split);
+            let right = tree(*split + 1..1000);
+            let joined = RedBlackTree::join(left, *split, right);
+
+            assert_invariants(&joined);
+            assert_eq!(sorted(&joined), (0..1000).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn split_halves_are_valid_after_blackening_roots() {
+        let (mut less, found, mut greater) = tree(0..1000).split(&400);
+        less.set_color(Color::Black);
+        greater.set_color(Color::Black);
+
+        assert_eq!(found, Some(400));
+        assert_invariants(&less);
+        assert_invariants(&greater);
+        assert_eq!(sorted(&less), (0..400).collect::<Vec<_>>());
+        assert_eq!(sorted(&greater), (401..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn bulk_set_operations_match_btree_set() {
+        use std::collections::BTreeSet;
+
+        let a_items: Vec<_> = (0..2000).map(|i| (i * 37) % 1511).collect();
+        let b_items: Vec<_> = (0..300).map(|i| (i * 53) % 1999).collect();
+        let a_set: BTreeSet<_> = a_items.iter().copied().collect();
+        let b_set: BTreeSet<_> = b_items.iter().copied().collect();
+        let a = || tree(a_items.iter().copied());
+        let b = || tree(b_items.iter().copied());
+
+        let union = a().union(b());
+        assert_invariants(&union);
+        assert_eq!(
+            sorted(&union),
+            a_set.union(&b_set).copied().collect::<Vec<_>>()
+        );
+
+        let intersection = a().intersection(b());
+        assert_invariants(&intersection);
+        assert_eq!(
+            sorted(&intersection),
+            a_set.intersection(&b_set).copied().collect::<Vec<_>>()
+        );
+
+        let difference = b().difference(a());
+        assert_invariants(&difference);
+        assert_eq!(
+            sorted(&difference),
+            b_set.difference(&a_set).copied().collect::<Vec<_>>()
+        );
+
+        let symmetric_difference = a().symmetric_difference(b());
+        assert_invariants(&symmetric_difference);
+        assert_eq!(
+            sorted(&symmetric_difference),
+            a_set
+                .symmetric_difference(&b_set)
+                .copied()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn bulk_set_operations_with_empty_trees() {
+        assert_eq!(
+            sorted(&tree(0..3).union(RedBlackTree::new())),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            sorted(&RedBlackTree::new().union(tree(0..3))),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            sorted(&tree(0..3).intersection(RedBlackTree::new())),
+            vec![]
+        );
+        assert_eq!(
+            sorted(&tree(0..3).difference(RedBlackTree::new())),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            sorted(&RedBlackTree::new().symmetric_difference(tree(0..3))),
+            vec![0, 1, 2]
+        );
+    }
+}
@@ -0,0 +1,464 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::mem;
+
+struct Node<T: Ord> {
+    item: T,
+    left: SplayTree<T>,
+    right: SplayTree<T>,
+}
+
+impl<T: Ord> Node<T> {
+    fn new(item: T) -> Self {
+        Node {
+            item,
+            left: SplayTree::new(),
+            right: SplayTree::new(),
+        }
+    }
+}
+
+/// A self-adjusting binary search tree
+///
+/// Every insertion, deletion and lookup through `find` moves the accessed
+/// item, or the last item visited while searching for it, to the root by a
+/// sequence of rotations called splaying. No balance information is stored,
+/// but any sequence of m operations on a tree of n items takes
+/// O((m + n) log n) time. Frequently accessed items stay close to the root,
+/// which makes the tree fast for skewed access patterns.
+///
+/// Since splaying modifies the tree, `find` takes `&mut self`. `contains`
+/// searches without splaying, and therefore without the amortized guarantee.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::splay_tree::SplayTree;
+///
+/// let mut tree = SplayTree::new();
+/// for i in 0..100 {
+///     tree.insert(i);
+/// }
+/// assert_eq!(tree.find(&42), Some(&42));
+/// assert_eq!(tree.root(), Some(&42));
+///
+/// tree.delete(42);
+/// assert!(!tree.contains(&42));
+/// ```
+pub struct SplayTree<T: Ord> {
+    node: Option<Box<Node<T>>>,
+}
+
+impl<T: Ord> Default for SplayTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Drop for SplayTree<T> {
+    fn drop(&mut self) {
+        // Frees the nodes one at a time, since dropping them recursively could overflow the stack
+        let mut nodes: Vec<_> = self.node.take().into_iter().collect();
+        while let Some(mut node) = nodes.pop() {
+            nodes.extend(node.left.node.take());
+            nodes.extend(node.right.node.take());
+        }
+    }
+}
+
+impl<T: Ord> SplayTree<T> {
+    pub fn new() -> Self {
+        Self { node: None }
+    }
+
+    /// Returns the item at the root, which is the most recently accessed one
+    pub fn root(&self) -> Option<&T> {
+        self.node.as_ref().map(|node| &node.item)
+    }
+
+    /// Moves `item` to the root, or the last item visited while searching for it
+    ///
+    /// This is top-down splaying: nodes on the search path are split off into
+    /// a left tree of smaller items and a right tree of larger items, which are
+    /// joined below the new root at the end. Pairs of steps in the same
+    /// direction rotate first, which is what keeps the amortized cost low.
+    fn splay<Q>(&mut self, item: &Q)
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut root = match self.node.take() {
+            Some(root) => root,
+            None => return,
+        };
+        let mut left = Self::new();
+        let mut right = Self::new();
+        // Where the next node larger than `item` goes in `right`, and the next smaller in `left`
+        let mut right_hole = &mut right;
+        let mut left_hole = &mut left;
+        loop {
+            match item.cmp(root.item.borrow()) {
+                Ordering::Less => {
+                    let mut child = match root.left.node.take() {
+                        Some(child) => child,
+                        None => break,
+                    };
+                    if item < child.item.borrow() {
+                        root.left.node = child.right.node.take();
+                        child.right.node = Some(root);
+                        root = child;
+                        child = match root.left.node.take() {
+                            Some(child) => child,
+                            None => break,
+                        };
+                    }
+                    right_hole.node = Some(root);
+                    right_hole = &mut right_hole.node.as_mut().unwrap().left;
+                    root = child;
+                }
+                Ordering::Greater => {
+                    let mut child = match root.right.node.take() {
+                        Some(child) => child,
+                        None => break,
+                    };
+                    if item > child.item.borrow() {
+                        root.right.node = child.left.node.take();
+                        child.left.node = Some(root);
+                        root = child;
+                        child = match root.right.node.take() {
+                            Some(child) => child,
+                            None => break,
+                        };
+                    }
+                    left_hole.node = Some(root);
+                    left_hole = &mut left_hole.node.as_mut().unwrap().right;
+                    root = child;
+                }
+                Ordering::Equal => break,
+            }
+        }
+        left_hole.node = root.left.node.take();
+        right_hole.node = root.right.node.take();
+        root.left = left;
+        root.right = right;
+        self.node = Some(root);
+    }
+
+    pub fn insert(&mut self, item: T) {
+        self.splay(&item);
+        let mut root = match self.node.take() {
+            Some(root) => root,
+            None => {
+                self.node = Some(Box::new(Node::new(item)));
+                return;
+            }
+        };
+        let mut node = Node::new(item);
+        match node.item.cmp(&root.item) {
+            Ordering::Less => {
+                node.left.node = root.left.node.take();
+                node.right.node = Some(root);
+            }
+            Ordering::Greater => {
+                node.right.node = root.right.node.take();
+                node.left.node = Some(root);
+            }
+            Ordering::Equal => {
+                self.node = Some(root);
+                return;
+            }
+        }
+        self.node = Some(Box::new(node));
+    }
+
+    pub fn delete(&mut self, item: T) {
+        self.remove(&item);
+    }
+
+    /// Removes the item equal to `item` and returns it
+    pub fn remove<Q>(&mut self, item: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(item)?;
+        let mut root = self.node.take().unwrap();
+        let mut left = mem::take(&mut root.left);
+        if left.node.is_some() {
+            // All items on the left are smaller, so the largest of them ends up at the root without a right child
+            left.splay(item);
+            left.node.as_mut().unwrap().right = mem::take(&mut root.right);
+            *self = left;
+        } else {
+            *self = mem::take(&mut root.right);
+        }
+        Some(root.item)
+    }
+
+    /// Searches for `item` and splays the tree, returning the item if it was found
+    pub fn find<Q>(&mut self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.splay(item);
+        self.node
+            .as_ref()
+            .map(|node| &node.item)
+            .filter(|root| (*root).borrow() == item)
+    }
+
+    /// Searches for `item` without changing the tree
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut tree = self;
+        while let Some(ref node) = tree.node {
+            match item.cmp(node.item.borrow()) {
+                Ordering::Greater => tree = &node.right,
+                Ordering::Less => tree = &node.left,
+                Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+
+    /// Returns an iterator over the items in sorted order
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left_spine(self);
+        iter
+    }
+}
+
+/// An in-order iterator over the items of a `SplayTree`
+pub struct Iter<'a, T: Ord> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T: Ord> Iter<'a, T> {
+    fn push_left_spine(&mut self, mut tree: &'a SplayTree<T>) {
+        while let Some(ref node) = tree.node {
+            self.stack.push(node);
+            tree = &node.left;
+        }
+    }
+}
+
+impl<'a, T: Ord> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        Some(&node.item)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a SplayTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::thread;
+
+    /// Returns the depth of `item`, counting the root as depth 0
+    fn depth<T: Ord>(tree: &SplayTree<T>, item: &T) -> Option<usize> {
+        let mut tree = tree;
+        let mut depth = 0;
+        while let Some(ref node) = tree.node {
+            match item.cmp(&node.item) {
+                Ordering::Greater => tree = &node.right,
+                Ordering::Less => tree = &node.left,
+                Ordering::Equal => return Some(depth),
+            }
+            depth += 1;
+        }
+        None
+    }
+
+    fn assert_sorted<T: Ord + std::fmt::Debug>(tree: &SplayTree<T>) {
+        let items: Vec<_> = tree.iter().collect();
+        assert!(
+            items.windows(2).all(|pair| pair[0] < pair[1]),
+            "Items are out of order: {:?}",
+            items
+        );
+    }
+
+    #[test]
+    fn empty_tree_doesnt_contain_element() {
+        let mut tree: SplayTree<i32> = SplayTree::new();
+        assert!(!tree.contains(&1));
+        assert_eq!(tree.find(&1), None);
+        assert_eq!(tree.root(), None);
+    }
+
+    #[test]
+    fn inserted_item_is_splayed_to_root() {
+        let mut tree = SplayTree::new();
+        for i in &[5, 2, 8, 1, 9, 3] {
+            tree.insert(*i);
+            assert_eq!(tree.root(), Some(i));
+        }
+        assert_sorted(&tree);
+        assert_eq!(
+            tree.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 5, 8, 9]
+        );
+    }
+
+    #[test]
+    fn duplicate_insert_is_ignored() {
+        let mut tree = SplayTree::new();
+        tree.insert(1);
+        tree.insert(2);
+        tree.insert(1);
+        tree.delete(1);
+
+        assert!(!tree.contains(&1));
+        assert_eq!(tree.iter().count(), 1);
+    }
+
+    #[test]
+    fn find_splays_found_item_to_root() {
+        let mut tree = SplayTree::new();
+        for i in 0..100 {
+            tree.insert(i);
+        }
+        assert_eq!(tree.find(&0), Some(&0));
+        assert_eq!(tree.root(), Some(&0));
+        assert_sorted(&tree);
+    }
+
+    #[test]
+    fn find_missing_item_splays_neighbor() {
+        let mut tree = SplayTree::new();
+        for i in (0..100).map(|i| 2 * i) {
+            tree.insert(i);
+        }
+        assert_eq!(tree.find(&51), None);
+        let root = *tree.root().unwrap();
+        assert!(root == 50 || root == 52);
+        assert_sorted(&tree);
+    }
+
+    #[test]
+    fn splaying_halves_depth_of_degenerate_path() {
+        // Sorted inserts leave a path of length n
+        let n = 1024;
+        let mut tree = SplayTree::new();
+        for i in 0..n {
+            tree.insert(i);
+        }
+        assert_eq!(depth(&tree, &0), Some(n - 1));
+
+        tree.find(&0);
+        assert!(depth(&tree, &(n - 1)).unwrap() <= n / 2 + 1);
+    }
+
+    #[test]
+    fn delete_items() {
+        let mut tree = SplayTree::new();
+        for i in &[5, 2, 8, 1, 9, 3, 7] {
+            tree.insert(*i);
+        }
+        tree.delete(5);
+        tree.delete(1);
+        tree.delete(9);
+        tree.delete(4);
+
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), vec![2, 3, 7, 8]);
+        assert_sorted(&tree);
+    }
+
+    #[test]
+    fn remove_returns_the_item_and_lookups_take_borrowed_forms() {
+        let mut tree = SplayTree::new();
+        for item in &["b", "a", "d", "c"] {
+            tree.insert(item.to_string());
+        }
+
+        assert!(tree.contains("a"));
+        assert!(!tree.contains("e"));
+        assert_eq!(tree.find("c").map(String::as_str), Some("c"));
+
+        assert_eq!(tree.remove("b"), Some(String::from("b")));
+        assert_eq!(tree.remove("b"), None);
+        assert!(!tree.contains("b"));
+        assert_eq!(tree.iter().count(), 3);
+        assert_sorted(&tree);
+    }
+
+    #[test]
+    fn delete_everything() {
+        let mut tree = SplayTree::new();
+        for i in 0..100 {
+            tree.insert(i);
+        }
+        for i in 0..100 {
+            tree.delete((i * 37) % 100);
+        }
+        assert_eq!(tree.root(), None);
+    }
+
+    #[test]
+    fn deep_trees_do_not_overflow_the_stack() {
+        // Recursing along a path as long as the tree would overflow this stack
+        let n = 20_000;
+        thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(move || {
+                let mut tree = SplayTree::new();
+                for i in 0..n {
+                    tree.insert(i);
+                }
+                assert_eq!(tree.find(&0), Some(&0));
+                tree.delete(n / 2);
+                assert_eq!(tree.iter().count(), n - 1);
+
+                // Sorted insertions leave a single spine to drop
+                let mut spine = SplayTree::new();
+                for i in 0..n {
+                    spine.insert(i);
+                }
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn matches_btree_set() {
+        let mut tree = SplayTree::new();
+        let mut set = BTreeSet::new();
+        let mut x: u64 = 1;
+        for _ in 0..5000 {
+            x = x.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let item = (x >> 33) % 500;
+            match (x >> 20) % 3 {
+                0 => {
+                    tree.insert(item);
+                    set.insert(item);
+                }
+                1 => {
+                    tree.delete(item);
+                    set.remove(&item);
+                }
+                _ => assert_eq!(tree.find(&item).is_some(), set.contains(&item)),
+            }
+        }
+        assert_sorted(&tree);
+        assert!(tree.iter().eq(set.iter()));
+    }
+}
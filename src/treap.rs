@@ -0,0 +1,502 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    item: T,
+    // Nodes have higher priorities than their children
+    priority: u64,
+    // Number of items in the subtree rooted at this node
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T> Node<T> {
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+fn leftmost<T>(mut link: &Link<T>) -> Option<&T> {
+    let mut item = None;
+    while let Some(ref node) = link {
+        item = Some(&node.item);
+        link = &node.left;
+    }
+    item
+}
+
+fn rightmost<T>(mut link: &Link<T>) -> Option<&T> {
+    let mut item = None;
+    while let Some(ref node) = link {
+        item = Some(&node.item);
+        link = &node.right;
+    }
+    item
+}
+
+/// Splits a subtree into the items for which `goes_left` holds and the rest
+///
+/// `goes_left` must hold for a prefix of the items in sorted order.
+fn split<T, F: Fn(&T) -> bool>(link: Link<T>, goes_left: &F) -> (Link<T>, Link<T>) {
+    match link {
+        None => (None, None),
+        Some(mut node) => {
+            if goes_left(&node.item) {
+                let (left, right) = split(node.right.take(), goes_left);
+                node.right = left;
+                node.update_size();
+                (Some(node), right)
+            } else {
+                let (left, right) = split(node.left.take(), goes_left);
+                node.left = right;
+                node.update_size();
+                (left, Some(node))
+            }
+        }
+    }
+}
+
+/// Merges two subtrees, given that every item of `left` is less than every item of `right`
+fn merge<T>(left: Link<T>, right: Link<T>) -> Link<T> {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+        (Some(mut left), Some(mut right)) => {
+            if left.priority > right.priority {
+                left.right = merge(left.right.take(), Some(right));
+                left.update_size();
+                Some(left)
+            } else {
+                right.left = merge(Some(left), right.left.take());
+                right.update_size();
+                Some(right)
+            }
+        }
+    }
+}
+
+/// Combines two subtrees with arbitrary items, keeping one of two equal items
+fn union<T: Ord>(a: Link<T>, b: Link<T>) -> Link<T> {
+    let (mut root, other) = match (a, b) {
+        (None, b) => return b,
+        (a, None) => return a,
+        (Some(a), Some(b)) if a.priority >= b.priority => (a, b),
+        (Some(a), Some(b)) => (b, a),
+    };
+    let (less, rest) = split(Some(other), &|item: &T| *item < root.item);
+    let (_, greater) = split(rest, &|item: &T| *item <= root.item);
+    root.left = union(root.left.take(), less);
+    root.right = union(root.right.take(), greater);
+    root.update_size();
+    Some(root)
+}
+
+/// A randomized binary search tree
+///
+/// Every item gets a random priority, and the tree is kept in heap order with
+/// respect to the priorities. The shape of the tree is then the same as if
+/// the items had been inserted in random order, regardless of the actual
+/// order, which gives an expected height of O(log n).
+///
+/// Insertion and removal split the tree around the item and merge the parts
+/// back together. Splitting and merging both take expected O(log n) time,
+/// and are also exposed as `split_off` and `append`. The priorities are drawn from a
+/// seedable generator, so `with_seed` gives reproducible trees.
+///
+/// # Examples
+///
+/// ```
+/// use algorithms::treap::Treap;
+///
+/// let mut treap = Treap::with_seed(7);
+/// for i in 0..1000 {
+///     treap.insert(i);
+/// }
+/// let upper = treap.split_off(&500);
+///
+/// assert_eq!(treap.len(), 500);
+/// assert_eq!(upper.min(), Some(&500));
+/// ```
+pub struct Treap<T: Ord> {
+    root: Link<T>,
+    rng: StdRng,
+}
+
+impl<T: Ord> Default for Treap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Drop for Treap<T> {
+    fn drop(&mut self) {
+        // Unlucky priorities can make a treap deep, so frees the nodes one at a time
+        let mut nodes: Vec<_> = self.root.take().into_iter().collect();
+        while let Some(mut node) = nodes.pop() {
+            nodes.extend(node.left.take());
+            nodes.extend(node.right.take());
+        }
+    }
+}
+
+impl<T: Ord> Treap<T> {
+    /// Creates an empty treap with priorities seeded from the operating system
+    pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    /// Creates an empty treap whose priorities are determined by `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> Self {
+        Self { root: None, rng }
+    }
+
+    /// Returns the number of items in the treap
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the stored item equal to `item`
+    pub fn get<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &self.root;
+        while let Some(ref node) = link {
+            match item.cmp(node.item.borrow()) {
+                Ordering::Greater => link = &node.right,
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.item),
+            }
+        }
+        None
+    }
+
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(item).is_some()
+    }
+
+    pub fn insert(&mut self, item: T) {
+        if self.contains(&item) {
+            return;
+        }
+        let node = Box::new(Node {
+            item,
+            priority: self.rng.gen(),
+            size: 1,
+            left: None,
+            right: None,
+        });
+        let (less, greater) = split(self.root.take(), &|other: &T| *other < node.item);
+        self.root = merge(merge(less, Some(node)), greater);
+    }
+
+    /// Removes the item equal to `item` and returns it
+    pub fn remove<Q>(&mut self, item: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (less, rest) = split(self.root.take(), &|other: &T| other.borrow() < item);
+        let (equal, greater) = split(rest, &|other: &T| other.borrow() <= item);
+        self.root = merge(less, greater);
+        equal.map(|node| node.item)
+    }
+
+    pub fn delete(&mut self, item: T) {
+        self.remove(&item);
+    }
+
+    /// Returns the smallest item
+    pub fn min(&self) -> Option<&T> {
+        leftmost(&self.root)
+    }
+
+    /// Returns the largest item
+    pub fn max(&self) -> Option<&T> {
+        rightmost(&self.root)
+    }
+
+    /// Returns the number of items strictly less than `item`
+    pub fn rank<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut rank = 0;
+        let mut link = &self.root;
+        while let Some(ref node) = link {
+            if node.item.borrow() < item {
+                rank += 1 + size(&node.left);
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+        rank
+    }
+
+    /// Returns the `k`:th smallest item, counting from zero
+    pub fn select(&self, mut k: usize) -> Option<&T> {
+        let mut link = &self.root;
+        while let Some(ref node) = link {
+            let left_size = size(&node.left);
+            match k.cmp(&left_size) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.item),
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    link = &node.right;
+                }
+            }
+        }
+        None
+    }
+
+    /// Moves every item greater than or equal to `item` into a new treap
+    ///
+    /// The new treap draws its priorities from a generator seeded by this one.
+    pub fn split_off<Q>(&mut self, item: &Q) -> Self
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (less, greater) = split(self.root.take(), &|other: &T| other.borrow() < item);
+        self.root = less;
+        Self {
+            root: greater,
+            rng: StdRng::from_rng(&mut self.rng).unwrap(),
+        }
+    }
+
+    /// Moves every item of `other` into this treap, leaving `other` empty
+    ///
+    /// If all items of one treap are less than all items of the other, the
+    /// two are merged along their facing spines in expected O(log n) time.
+    /// Otherwise the treaps are combined by repeatedly splitting one around the
+    /// root of the other, in expected O(m log(n / m + 1)) time.
+    pub fn append(&mut self, other: &mut Self) {
+        let root = self.root.take();
+        let other = other.root.take();
+        self.root = if root.is_none() || other.is_none() {
+            root.or(other)
+        } else if rightmost(&root) < leftmost(&other) {
+            merge(root, other)
+        } else if rightmost(&other) < leftmost(&root) {
+            merge(other, root)
+        } else {
+            union(root, other)
+        };
+    }
+
+    /// Returns an iterator over the items in sorted order
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+}
+
+/// An in-order iterator over the items of a `Treap`
+pub struct Iter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left_spine(&mut self, mut link: &'a Link<T>) {
+        while let Some(ref node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        Some(&node.item)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a Treap<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// Checks ordering, heap order of priorities and sizes, and returns the height
+    fn check_subtree<T: Ord>(link: &Link<T>, lower: Option<&T>, upper: Option<&T>) -> usize {
+        let node = match link {
+            Some(node) => node,
+            None => return 0,
+        };
+        assert!(lower.map_or(true, |lower| node.item > *lower));
+        assert!(upper.map_or(true, |upper| node.item < *upper));
+        for child in node.left.iter().chain(node.right.iter()) {
+            assert!(child.priority <= node.priority);
+        }
+        assert_eq!(node.size, 1 + size(&node.left) + size(&node.right));
+        let left = check_subtree(&node.left, lower, Some(&node.item));
+        let right = check_subtree(&node.right, Some(&node.item), upper);
+        1 + left.max(right)
+    }
+
+    fn assert_invariants<T: Ord>(treap: &Treap<T>) -> usize {
+        check_subtree(&treap.root, None, None)
+    }
+
+    fn treap(seed: u64, items: impl IntoIterator<Item = i32>) -> Treap<i32> {
+        let mut treap = Treap::with_seed(seed);
+        for item in items {
+            treap.insert(item);
+        }
+        treap
+    }
+
+    fn collect(treap: &Treap<i32>) -> Vec<i32> {
+        treap.iter().copied().collect()
+    }
+
+    #[test]
+    fn empty_treap() {
+        let treap = treap(0, vec![]);
+        assert!(treap.is_empty());
+        assert!(!treap.contains(&1));
+        assert_eq!(treap.min(), None);
+        assert_eq!(treap.select(0), None);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut treap = treap(1, vec![5, 2, 8, 1, 9, 3, 2]);
+        assert_eq!(treap.len(), 6);
+        assert_eq!(treap.remove(&5), Some(5));
+        assert_eq!(treap.remove(&5), None);
+        treap.delete(1);
+
+        assert_eq!(collect(&treap), vec![2, 3, 8, 9]);
+        assert_eq!(treap.min(), Some(&2));
+        assert_eq!(treap.max(), Some(&9));
+        assert_invariants(&treap);
+    }
+
+    #[test]
+    fn same_seed_gives_same_shape() {
+        let shape = |treap: &Treap<i32>| {
+            let mut shape = Vec::new();
+            let mut stack: Vec<&Link<i32>> = vec![&treap.root];
+            while let Some(link) = stack.pop() {
+                shape.push(link.as_ref().map(|node| node.item));
+                if let Some(node) = link {
+                    stack.push(&node.right);
+                    stack.push(&node.left);
+                }
+            }
+            shape
+        };
+        assert_eq!(shape(&treap(3, 0..100)), shape(&treap(3, 0..100)));
+        assert_ne!(shape(&treap(3, 0..100)), shape(&treap(4, 0..100)));
+    }
+
+    #[test]
+    fn sorted_inserts_give_logarithmic_height() {
+        let n = 10_000;
+        let treap = treap(5, 0..n);
+        let height = assert_invariants(&treap);
+        // The expected height is about 3 log2(n)
+        assert!(height < 4 * 14, "Height {} is too large", height);
+    }
+
+    #[test]
+    fn rank_and_select() {
+        let treap = treap(6, (0..100).map(|i| 3 * i));
+        for k in 0..100 {
+            assert_eq!(treap.select(k), Some(&(3 * k as i32)));
+            assert_eq!(treap.rank(&(3 * k as i32)), k);
+            assert_eq!(treap.rank(&(3 * k as i32 + 1)), k + 1);
+        }
+        assert_eq!(treap.select(100), None);
+    }
+
+    #[test]
+    fn split_off_and_append_disjoint() {
+        let mut low = treap(7, 0..1000);
+        let mut high = low.split_off(&600);
+        assert_eq!(low.len(), 600);
+        assert_eq!(high.len(), 400);
+        assert_eq!(high.min(), Some(&600));
+        assert_invariants(&low);
+        assert_invariants(&high);
+
+        high.append(&mut low);
+        assert!(low.is_empty());
+        assert_eq!(collect(&high), (0..1000).collect::<Vec<_>>());
+        assert_invariants(&high);
+    }
+
+    #[test]
+    fn append_overlapping() {
+        let mut a = treap(8, (0..300).map(|i| 2 * i));
+        let mut b = treap(9, (0..300).map(|i| 3 * i));
+        a.append(&mut b);
+
+        let expected: BTreeSet<_> = (0..300)
+            .map(|i| 2 * i)
+            .chain((0..300).map(|i| 3 * i))
+            .collect();
+        assert_eq!(collect(&a), expected.into_iter().collect::<Vec<_>>());
+        assert_invariants(&a);
+    }
+
+    #[test]
+    fn matches_btree_set() {
+        let mut treap = Treap::with_seed(10);
+        let mut set = BTreeSet::new();
+        let mut x: u64 = 1;
+        for _ in 0..5000 {
+            x = x.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let item = ((x >> 33) % 500) as i32;
+            if x & (1 << 20) == 0 {
+                treap.insert(item);
+                set.insert(item);
+            } else {
+                assert_eq!(treap.remove(&item), set.take(&item));
+            }
+        }
+        assert_invariants(&treap);
+        assert!(treap.iter().eq(set.iter()));
+    }
+}